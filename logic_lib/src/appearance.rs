@@ -0,0 +1,172 @@
+use crate::types::{is_identifier, Component, ComponentType};
+
+/// Where the ports of a circuit sit when it is placed inside another circuit, relative to the
+/// anchor (`loc`) of an instance facing `facing`.
+#[derive(Debug, Clone)]
+pub(crate) struct Appearance {
+    pub facing: String,
+    pub ports: Vec<AppearancePort>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct AppearancePort {
+    /// Id of the pin inside the referenced circuit
+    pub pin: String,
+    /// Name of the instance port, see `port_name`
+    pub name: String,
+    pub offset: (i64, i64),
+    pub output: bool,
+}
+
+/// Lays the pins out the way Logisim's default appearance does: every pin goes on the edge
+/// opposite to the way it faces, sorted along that edge, and the anchor sits on the first
+/// non-empty edge out of east, north, west and south.
+pub(crate) fn default_appearance(components: &[Component]) -> Appearance {
+    let mut north: Vec<&Component> = Vec::new();
+    let mut south: Vec<&Component> = Vec::new();
+    let mut east: Vec<&Component> = Vec::new();
+    let mut west: Vec<&Component> = Vec::new();
+
+    for pin in components
+        .iter()
+        .filter(|component| component.component_type == ComponentType::PIN)
+    {
+        match pin.facing() {
+            "north" => south.push(pin),
+            "south" => north.push(pin),
+            "west" => east.push(pin),
+            _ => west.push(pin),
+        }
+    }
+
+    north.sort_by_key(|pin| (pin.loc.x, pin.loc.y));
+    south.sort_by_key(|pin| (pin.loc.x, pin.loc.y));
+    east.sort_by_key(|pin| (pin.loc.y, pin.loc.x));
+    west.sort_by_key(|pin| (pin.loc.y, pin.loc.x));
+
+    let (num_north, num_south) = (north.len() as i64, south.len() as i64);
+    let (num_east, num_west) = (east.len() as i64, west.len() as i64);
+    let max_vert = num_north.max(num_south);
+    let max_horz = num_east.max(num_west);
+
+    let offs_north = compute_offset(num_north, num_south, max_horz);
+    let offs_south = compute_offset(num_south, num_north, max_horz);
+    let offs_east = compute_offset(num_east, num_west, max_vert);
+    let offs_west = compute_offset(num_west, num_east, max_vert);
+
+    let width = compute_dimension(max_vert, max_horz);
+    let height = compute_dimension(max_horz, max_vert);
+
+    let anchor = if num_east > 0 {
+        (width, offs_east)
+    } else if num_north > 0 {
+        (offs_north, 0)
+    } else if num_west > 0 {
+        (0, offs_west)
+    } else if num_south > 0 {
+        (offs_south, height)
+    } else {
+        (0, 0)
+    };
+
+    let mut ports: Vec<AppearancePort> = Vec::new();
+    let edges = [
+        (north, (offs_north, 0), (10, 0)),
+        (south, (offs_south, height), (10, 0)),
+        (east, (width, offs_east), (0, 10)),
+        (west, (0, offs_west), (0, 10)),
+    ];
+
+    for (pins, (x, y), (dx, dy)) in edges {
+        for (i, pin) in pins.into_iter().enumerate() {
+            let i = i as i64;
+
+            ports.push(AppearancePort {
+                pin: pin.id.clone(),
+                name: port_name(components, pin),
+                offset: (x + dx * i - anchor.0, y + dy * i - anchor.1),
+                output: pin
+                    .attributes
+                    .get("output")
+                    .is_some_and(|output| output == "true"),
+            });
+        }
+    }
+
+    Appearance {
+        facing: String::from("east"),
+        ports,
+    }
+}
+
+/// Instance ports are named after the label of their pin, falling back to the pin's id when the
+/// label is missing, shared with another pin or not usable in `.logic` code.
+pub(crate) fn port_name(components: &[Component], pin: &Component) -> String {
+    let label = pin
+        .attributes
+        .get("label")
+        .filter(|label| is_identifier(label));
+
+    match label {
+        Some(label) => {
+            let unique = components
+                .iter()
+                .filter(|component| {
+                    component.component_type == ComponentType::PIN
+                        && component.attributes.get("label") == Some(label)
+                })
+                .count()
+                == 1;
+
+            if unique {
+                label.clone()
+            } else {
+                pin.id.clone()
+            }
+        }
+        None => pin.id.clone(),
+    }
+}
+
+fn compute_dimension(max_this: i64, max_others: i64) -> i64 {
+    if max_this < 3 {
+        30
+    } else if max_others == 0 {
+        10 * max_this
+    } else {
+        10 * max_this + 10
+    }
+}
+
+fn compute_offset(num_facing: i64, num_opposite: i64, max_others: i64) -> i64 {
+    let max_this = num_facing.max(num_opposite);
+    let max_offs = match max_this {
+        0 | 1 if max_others == 0 => 15,
+        0..=2 => 10,
+        _ if max_others == 0 => 5,
+        _ => 10,
+    };
+
+    max_offs + 10 * ((max_this - num_facing) / 2)
+}
+
+/// Rotates an offset drawn for an instance facing `from` so it matches one facing `to`.
+pub(crate) fn rotate(offset: (i64, i64), from: &str, to: &str) -> (i64, i64) {
+    // Quarter turns counterclockwise, starting from east
+    let quarter_turns = |facing: &str| -> i32 {
+        match facing {
+            "north" => 1,
+            "west" => 2,
+            "south" => 3,
+            _ => 0,
+        }
+    };
+
+    let mut offset = offset;
+    for _ in 0..(quarter_turns(to) - quarter_turns(from)).rem_euclid(4) {
+        // The y axis points down, so a counterclockwise turn maps (x, y) to (y, -x)
+        offset = (offset.1, -offset.0);
+    }
+
+    offset
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+/// A node of a reduced ordered binary decision diagram: the function is `high` where
+/// `variable` is true and `low` where it is false.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+struct Node {
+    variable: usize,
+    low: usize,
+    high: usize,
+}
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+enum Operation {
+    And,
+    Or,
+    Xor,
+}
+
+/// Shared storage for reduced ordered BDDs over variables `0..variables`, tested in that
+/// order. Functions are node indices, so two functions are equal exactly when their indices
+/// are.
+pub(crate) struct Bdd {
+    variables: usize,
+    nodes: Vec<Node>,
+    unique: HashMap<Node, usize>,
+    computed: HashMap<(Operation, usize, usize), usize>,
+}
+
+impl Bdd {
+    pub const FALSE: usize = 0;
+    pub const TRUE: usize = 1;
+
+    pub fn new(variables: usize) -> Bdd {
+        // The terminals sit past every variable so they always come last in the order
+        let terminal = |value| Node {
+            variable: variables,
+            low: value,
+            high: value,
+        };
+
+        Bdd {
+            variables,
+            nodes: vec![terminal(Bdd::FALSE), terminal(Bdd::TRUE)],
+            unique: HashMap::new(),
+            computed: HashMap::new(),
+        }
+    }
+
+    pub fn variable(&mut self, variable: usize) -> usize {
+        self.node(variable, Bdd::FALSE, Bdd::TRUE)
+    }
+
+    pub fn not(&mut self, f: usize) -> usize {
+        self.xor(f, Bdd::TRUE)
+    }
+
+    pub fn and(&mut self, f: usize, g: usize) -> usize {
+        self.apply(Operation::And, f, g)
+    }
+
+    pub fn or(&mut self, f: usize, g: usize) -> usize {
+        self.apply(Operation::Or, f, g)
+    }
+
+    pub fn xor(&mut self, f: usize, g: usize) -> usize {
+        self.apply(Operation::Xor, f, g)
+    }
+
+    /// Values of every variable making `f` true, with the variables it does not depend on
+    /// false, or `None` if `f` is always false.
+    pub fn satisfy(&self, f: usize) -> Option<Vec<bool>> {
+        if f == Bdd::FALSE {
+            return None;
+        }
+
+        let mut values = vec![false; self.variables];
+        let mut node = f;
+        while node != Bdd::TRUE {
+            let Node {
+                variable,
+                low,
+                high,
+            } = self.nodes[node];
+            // Every node other than the false terminal has a path to true
+            if low == Bdd::FALSE {
+                values[variable] = true;
+                node = high;
+            } else {
+                node = low;
+            }
+        }
+
+        Some(values)
+    }
+
+    pub fn evaluate(&self, f: usize, values: &[bool]) -> bool {
+        let mut node = f;
+        while node > Bdd::TRUE {
+            let Node {
+                variable,
+                low,
+                high,
+            } = self.nodes[node];
+            node = if values[variable] { high } else { low };
+        }
+
+        node == Bdd::TRUE
+    }
+
+    fn node(&mut self, variable: usize, low: usize, high: usize) -> usize {
+        if low == high {
+            return low;
+        }
+
+        let node = Node {
+            variable,
+            low,
+            high,
+        };
+        if let Some(index) = self.unique.get(&node) {
+            return *index;
+        }
+
+        self.nodes.push(node);
+        self.unique.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn apply(&mut self, operation: Operation, f: usize, g: usize) -> usize {
+        if f <= Bdd::TRUE && g <= Bdd::TRUE {
+            let (f, g) = (f == Bdd::TRUE, g == Bdd::TRUE);
+            let value = match operation {
+                Operation::And => f && g,
+                Operation::Or => f || g,
+                Operation::Xor => f != g,
+            };
+            return usize::from(value);
+        }
+
+        // All three operations are commutative
+        let key = (operation, f.min(g), f.max(g));
+        if let Some(result) = self.computed.get(&key) {
+            return *result;
+        }
+
+        let (f_node, g_node) = (self.nodes[f], self.nodes[g]);
+        let variable = f_node.variable.min(g_node.variable);
+        let cofactors = |node: Node, index: usize| {
+            if node.variable == variable {
+                (node.low, node.high)
+            } else {
+                (index, index)
+            }
+        };
+        let (f_low, f_high) = cofactors(f_node, f);
+        let (g_low, g_high) = cofactors(g_node, g);
+
+        let low = self.apply(operation, f_low, g_low);
+        let high = self.apply(operation, f_high, g_high);
+        let result = self.node(variable, low, high);
+
+        self.computed.insert(key, result);
+        result
+    }
+}
@@ -1,80 +1,298 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::Infallible,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use xmltree::Element;
 
-use crate::types::{Component, ComponentType, Coordinate, ModuleType, Wire};
+use crate::appearance::{default_appearance, port_name, rotate, Appearance, AppearancePort};
+use crate::circuit::{Circuit, Net};
+use crate::diagnostics::{Diagnostic, Diagnostics, Parsed, Severity};
+use crate::error::ParseError;
+use crate::project::{GateUndefined, Options, Project};
+use crate::types::{Component, ComponentType, Coordinate, Port, PortDirection, Wire};
+use crate::union_find::UnionFind;
+
+enum Source {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
 
-pub struct CircParser<'a> {
-    file_path: &'a Path,
-    components: Vec<Component>,
-    wires: Vec<Wire>,
-    components_map: HashMap<Coordinate, String>,
+pub struct CircParser {
+    source: Source,
+    file_path: Option<PathBuf>,
+    project: Option<Project>,
+    current_circuit: Option<String>,
+    appearances: HashMap<String, Appearance>,
     wires_map: HashMap<Coordinate, Vec<Coordinate>>,
-    visited_nodes: Vec<Coordinate>,
+    diagnostics: Diagnostics,
 }
 
-impl CircParser<'_> {
-    pub fn new(file_path: &Path) -> CircParser {
+impl CircParser {
+    /// Creates a parser that reads the circuit from `file_path` when `parse` is called.
+    pub fn new(file_path: impl AsRef<Path>) -> CircParser {
+        let file_path = file_path.as_ref().to_path_buf();
+
+        CircParser::with_source(Source::File(file_path.clone()), Some(file_path))
+    }
+
+    /// Creates a parser over the raw contents of a `.circ` file.
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> CircParser {
+        CircParser::with_source(Source::Bytes(bytes.into()), None)
+    }
+
+    /// Creates a parser over everything `reader` yields, e.g. stdin or a zip entry.
+    pub fn from_reader(mut reader: impl Read) -> Result<CircParser, ParseError> {
+        let mut bytes: Vec<u8> = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|source| ParseError::Io { file: None, source })?;
+
+        Ok(CircParser::from_bytes(bytes))
+    }
+
+    /// Sets the file name reported in errors and diagnostics for in-memory sources.
+    pub fn with_file_name(mut self, file_name: impl Into<PathBuf>) -> CircParser {
+        self.file_path = Some(file_name.into());
+        self
+    }
+
+    fn with_source(source: Source, file_path: Option<PathBuf>) -> CircParser {
         CircParser {
+            source,
             file_path,
-            components: Vec::new(),
-            wires: Vec::new(),
-            components_map: HashMap::new(),
+            project: None,
+            current_circuit: None,
+            appearances: HashMap::new(),
             wires_map: HashMap::new(),
-            visited_nodes: Vec::new(),
+            diagnostics: Diagnostics::new(),
         }
     }
 
-    pub fn parse(&mut self) {
-        let file_data = std::fs::read_to_string(self.file_path).expect("Failed to read file");
-        let parsed =
-            Element::parse(file_data.as_bytes()).unwrap_or_else(|_| panic!("Failed to parse"));
-        let circuit = parsed
-            .get_child("circuit")
-            .unwrap_or_else(|| panic!("Failed to get circuit"));
+    /// Warnings collected by the last parse, including the ones the `transpile_*` methods do.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Parses the project and returns its main circuit.
+    pub fn parse(&mut self) -> Result<Circuit, ParseError> {
+        let project = self.parse_project()?;
+
+        project
+            .main_circuit()
+            .cloned()
+            .ok_or_else(|| self.unknown_circuit(&project.main))
+    }
+
+    /// Like `parse`, also returning the warnings found, for parsers that are not kept around.
+    pub fn parse_with_diagnostics(&mut self) -> Result<Parsed<Circuit>, ParseError> {
+        let circuit = self.parse()?;
+
+        Ok(Parsed {
+            value: circuit,
+            diagnostics: self.diagnostics.clone(),
+        })
+    }
+
+    /// Parses every `<circuit>` of the project.
+    pub fn parse_project(&mut self) -> Result<Project, ParseError> {
+        let project = self.parse_document()?;
+        self.project = Some(project.clone());
+
+        Ok(project)
+    }
+
+    /// Like `parse_project`, also returning the warnings found.
+    pub fn parse_project_with_diagnostics(&mut self) -> Result<Parsed<Project>, ParseError> {
+        let project = self.parse_project()?;
 
+        Ok(Parsed {
+            value: project,
+            diagnostics: self.diagnostics.clone(),
+        })
+    }
+
+    fn parse_document(&mut self) -> Result<Project, ParseError> {
+        self.diagnostics.clear();
+        self.current_circuit = None;
+
+        let parsed = match &self.source {
+            Source::File(path) => {
+                let file_data = std::fs::read(path).map_err(|source| ParseError::Io {
+                    file: self.file_path.clone(),
+                    source,
+                })?;
+                Element::parse(file_data.as_slice())
+            }
+            Source::Bytes(bytes) => Element::parse(bytes.as_slice()),
+        }
+        .map_err(|source| ParseError::Xml {
+            file: self.file_path.clone(),
+            source,
+        })?;
+
+        // Subcircuit instances need the pin layout of the circuit they refer to, so every
+        // circuit is read before any of them is connected
+        let mut circuits: Vec<Circuit> = Vec::new();
+        self.appearances.clear();
+
+        for circuit_elem in parsed
+            .children
+            .iter()
+            .filter_map(|child| child.as_element())
+            .filter(|elem| elem.name == "circuit")
+        {
+            let name = self.get_attribute(circuit_elem, "name")?.to_string();
+            self.current_circuit = Some(name.clone());
+
+            let circuit = self.read_circuit(&name, circuit_elem)?;
+            let appearance = match circuit_elem.get_child("appear") {
+                Some(appear_elem) => self.read_appearance(appear_elem, &circuit)?,
+                None => default_appearance(&circuit.components),
+            };
+
+            self.appearances.insert(name, appearance);
+            circuits.push(circuit);
+        }
+        self.current_circuit = None;
+
+        for circuit in &circuits {
+            for component in &circuit.components {
+                if let ComponentType::Subcircuit(name) = &component.component_type {
+                    if !self.appearances.contains_key(name) {
+                        return Err(self.unknown_circuit(name));
+                    }
+                }
+            }
+        }
+
+        for circuit in &mut circuits {
+            self.current_circuit = Some(circuit.name.clone());
+            self.connect_circuit(circuit)?;
+        }
+        self.current_circuit = None;
+
+        let first_circuit = match circuits.first() {
+            Some(circuit) => circuit.name.clone(),
+            None => {
+                return Err(ParseError::MissingElement {
+                    file: self.file_path.clone(),
+                    element: String::from("circuit"),
+                })
+            }
+        };
+        let circuits: BTreeMap<String, Circuit> = circuits
+            .into_iter()
+            .map(|circuit| (circuit.name.clone(), circuit))
+            .collect();
+
+        let main = match parsed.get_child("main") {
+            Some(main_elem) => {
+                let main = self.get_attribute(main_elem, "name")?;
+
+                if circuits.contains_key(main) {
+                    main.to_string()
+                } else {
+                    self.report(
+                        Severity::Warning,
+                        None,
+                        None,
+                        format!(
+                            "main circuit {:?} does not exist, using {:?} instead",
+                            main, first_circuit
+                        ),
+                    );
+                    first_circuit
+                }
+            }
+            None => first_circuit,
+        };
+
+        let options = match parsed.get_child("options") {
+            Some(options_elem) => self.read_options(options_elem)?,
+            None => Options::default(),
+        };
+
+        Ok(Project {
+            circuits,
+            main,
+            options,
+        })
+    }
+
+    /// Reads the components and wires of a circuit without connecting them.
+    fn read_circuit(&mut self, name: &str, circuit_elem: &Element) -> Result<Circuit, ParseError> {
         let mut components: Vec<Component> = Vec::new();
         let mut wires: Vec<Wire> = Vec::new();
 
         let mut count = 0;
-        for child in &circuit.children {
-            let elem = child
-                .as_element()
-                .unwrap_or_else(|| panic!("Failed to get element"));
-
+        // Text, comments and processing instructions carry no circuit data
+        for elem in circuit_elem
+            .children
+            .iter()
+            .filter_map(|child| child.as_element())
+        {
             match elem.name.as_str() {
                 "comp" => {
-                    let lib = elem.attributes.get("lib").unwrap();
-                    let name = elem.attributes.get("name").unwrap().to_string();
-                    let loc = elem.attributes.get("loc").unwrap();
-                    let mut attributes: HashMap<String, String> = HashMap::new();
-                    let component_type: ComponentType = self.get_component_type(&name);
-
-                    for child in &elem.children {
-                        let elem = child
-                            .as_element()
-                            .unwrap_or_else(|| panic!("Failed to get element"));
-
-                        match elem.name.as_str() {
-                            "a" => {
-                                let name = elem.attributes.get("name").unwrap();
-                                let value = elem.attributes.get("val").unwrap();
-
-                                attributes.insert(String::from(name), String::from(value));
+                    let name = self.get_attribute(elem, "name")?.to_string();
+                    let loc = self.get_attribute(elem, "loc")?;
+                    let mut attributes: BTreeMap<String, String> = BTreeMap::new();
+
+                    // Components without a library are instances of another circuit of the
+                    // project
+                    let (lib, component_type) = match elem.attributes.get("lib") {
+                        Some(lib) => (
+                            Some(
+                                lib.parse::<u32>()
+                                    .map_err(|_| ParseError::InvalidAttribute {
+                                        file: self.file_path.clone(),
+                                        element: elem.name.clone(),
+                                        attribute: String::from("lib"),
+                                        value: lib.to_string(),
+                                    })?,
+                            ),
+                            self.get_component_type(&name)?,
+                        ),
+                        None => (None, ComponentType::Subcircuit(name.clone())),
+                    };
+                    let id = format!("comp_{}", count);
+
+                    for elem in elem.children.iter().filter_map(|child| child.as_element()) {
+                        if elem.name == "a" {
+                            let name = self.get_attribute(elem, "name")?;
+                            // Long values such as ROM contents are stored as text instead
+                            let value = match elem.attributes.get("val") {
+                                Some(value) => value.clone(),
+                                None => match elem.get_text() {
+                                    Some(text) => text.into_owned(),
+                                    None => self.get_attribute(elem, "val")?.to_string(),
+                                },
+                            };
+
+                            if !is_known_attribute(&component_type, name) {
+                                self.report(
+                                    Severity::Warning,
+                                    Some(&id),
+                                    None,
+                                    format!("unknown attribute `{}`", name),
+                                );
                             }
-                            _ => (),
+
+                            attributes.insert(String::from(name), value);
                         }
                     }
 
-                    let id = format!("comp_{}", count);
-
                     let component = Component {
-                        lib: u32::from_str_radix(lib, 10).unwrap(),
+                        lib,
                         name,
-                        loc: self.parse_string_to_coordinate(loc),
+                        loc: self.parse_string_to_coordinate(elem, "loc", loc)?,
                         id,
                         attributes,
                         component_type,
+                        ports: Vec::new(),
                     };
 
                     components.push(component);
@@ -82,12 +300,12 @@ impl CircParser<'_> {
                     count += 1;
                 }
                 "wire" => {
-                    let from = elem.attributes.get("from").unwrap();
-                    let to = elem.attributes.get("to").unwrap();
+                    let from = self.get_attribute(elem, "from")?;
+                    let to = self.get_attribute(elem, "to")?;
 
                     let wire = Wire {
-                        from: self.parse_string_to_coordinate(from),
-                        to: self.parse_string_to_coordinate(to),
+                        from: self.parse_string_to_coordinate(elem, "from", from)?,
+                        to: self.parse_string_to_coordinate(elem, "to", to)?,
                     };
 
                     wires.push(wire);
@@ -96,828 +314,607 @@ impl CircParser<'_> {
             }
         }
 
-        self.components = components;
-        self.wires = wires;
+        let mut circuit = Circuit::new(name);
+        for component in components {
+            circuit.add_component(component);
+        }
+        circuit.wires = wires;
 
-        self.map_components_by_location();
-        self.map_wires_by_location();
+        Ok(circuit)
     }
 
-    pub fn get_component_type(&mut self, name: &str) -> ComponentType {
-        match name {
-            "AND Gate" => ComponentType::AND,
-            "OR Gate" => ComponentType::OR,
-            "NAND Gate" => ComponentType::NAND,
-            "NOR Gate" => ComponentType::NOR,
-            "XOR Gate" => ComponentType::XOR,
-            "XNOR Gate" => ComponentType::XNOR,
-            "NOT Gate" => ComponentType::NOT,
-            "Pin" => ComponentType::PIN,
-            &_ => todo!("Component type not implemented!"),
+    /// Reads the `<a>` attributes of the `<options>` element that simulation depends on.
+    fn read_options(&self, options_elem: &Element) -> Result<Options, ParseError> {
+        let mut options = Options::default();
+
+        for elem in options_elem
+            .children
+            .iter()
+            .filter_map(|child| child.as_element())
+            .filter(|elem| elem.name == "a")
+        {
+            let name = self.get_attribute(elem, "name")?;
+            let value = self.get_attribute(elem, "val")?;
+            let invalid = || ParseError::InvalidAttribute {
+                file: self.file_path.clone(),
+                element: String::from("options"),
+                attribute: String::from(name),
+                value: String::from(value),
+            };
+
+            match name {
+                "gateUndefined" => {
+                    options.gate_undefined = match value {
+                        "ignore" => GateUndefined::Ignore,
+                        "error" => GateUndefined::Error,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "simlimit" => {
+                    options.sim_limit = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|limit| *limit > 0)
+                        .ok_or_else(invalid)?
+                }
+                "simrand" => options.sim_rand = value.parse::<u64>().map_err(|_| invalid())?,
+                _ => (),
+            }
         }
-    }
 
-    pub fn transpile_to_logic_code(&mut self) -> String {
-        let mut logic_code = String::new();
+        Ok(options)
+    }
 
-        // DEFINE COMPONENTS
-        for component in self.components.clone() {
-            // Syntax:
-            // #define <id>
-            // - type: <type>
-            // - <attribute>: <value>
-            // - <attribute>: <value>
-            // ...
+    /// Reads a custom `<appear>`ance: every `<circ-port>` names the pin it stands for, and the
+    /// `<circ-anchor>` is where the instance's `loc` lies.
+    fn read_appearance(
+        &mut self,
+        appear_elem: &Element,
+        circuit: &Circuit,
+    ) -> Result<Appearance, ParseError> {
+        let anchor_elem =
+            appear_elem
+                .get_child("circ-anchor")
+                .ok_or_else(|| ParseError::MissingElement {
+                    file: self.file_path.clone(),
+                    element: String::from("circ-anchor"),
+                })?;
+        let anchor = self.read_center(anchor_elem)?;
+        let facing = anchor_elem
+            .attributes
+            .get("facing")
+            .cloned()
+            .unwrap_or_else(|| String::from("east"));
+
+        let mut ports: Vec<AppearancePort> = Vec::new();
+        for port_elem in appear_elem
+            .children
+            .iter()
+            .filter_map(|child| child.as_element())
+            .filter(|elem| elem.name == "circ-port")
+        {
+            let pin_loc = self.get_attribute(port_elem, "pin")?;
+            let pin_loc = self.parse_string_to_coordinate(port_elem, "pin", pin_loc)?;
+            let position = self.read_center(port_elem)?;
+
+            let pin = circuit.components.iter().find(|component| {
+                component.component_type == ComponentType::PIN && component.loc == pin_loc
+            });
+
+            match pin {
+                Some(pin) => ports.push(AppearancePort {
+                    pin: pin.id.clone(),
+                    name: port_name(&circuit.components, pin),
+                    offset: (position.0 - anchor.0, position.1 - anchor.1),
+                    output: pin
+                        .attributes
+                        .get("output")
+                        .is_some_and(|output| output == "true"),
+                }),
+                None => self.report(
+                    Severity::Warning,
+                    None,
+                    Some(pin_loc),
+                    String::from("the appearance refers to a pin that does not exist"),
+                ),
+            }
+        }
 
-            // #attach <id> <id>
-            // #attach <id> <id>
-            // ...
+        Ok(Appearance { facing, ports })
+    }
 
-            logic_code.push_str(format!("#define {}\n", component.id).as_str());
-            logic_code.push_str(format!("- type: {:?}\n", component.component_type).as_str());
+    /// Center of an appearance shape given by its `x`, `y`, `width` and `height`.
+    fn read_center(&self, elem: &Element) -> Result<(i64, i64), ParseError> {
+        let mut values: Vec<i64> = Vec::new();
+
+        for attribute in ["x", "y", "width", "height"] {
+            let value = self.get_attribute(elem, attribute)?;
+            values.push(
+                value
+                    .parse::<i64>()
+                    .map_err(|_| ParseError::InvalidAttribute {
+                        file: self.file_path.clone(),
+                        element: elem.name.clone(),
+                        attribute: String::from(attribute),
+                        value: String::from(value),
+                    })?,
+            );
+        }
 
-            for (attribute, value) in component.attributes {
-                logic_code.push_str(format!("- {}: {}\n", attribute, value).as_str());
-            }
+        Ok((values[0] + values[2] / 2, values[1] + values[3] / 2))
+    }
 
-            logic_code.push_str("\n");
+    /// Works out the nets of a circuit and which components they connect.
+    fn connect_circuit(&mut self, circuit: &mut Circuit) -> Result<(), ParseError> {
+        for component in &mut circuit.components {
+            component.ports = self.calculate_ports(component)?;
         }
 
-        // CONNECT COMPONENTS
-        for component in self.components.clone() {
-            let destinations =
-                self.track_wire_destinations(ModuleType::Component(component.clone()));
+        self.wires_map = circuit.wire_graph();
 
-            for destination in destinations {
-                logic_code.push_str(
-                    format!("#attach {} {}\n", component.id.clone(), destination.clone()).as_str(),
-                );
-            }
+        circuit.nets = self.extract_nets(&circuit.components, &circuit.wires);
+        self.check_connectivity(circuit);
+        self.check_drivers(circuit);
+
+        circuit.derive_connections();
+
+        Ok(())
+    }
+
+    pub fn get_component_type(&mut self, name: &str) -> Result<ComponentType, ParseError> {
+        match name {
+            "AND Gate" => Ok(ComponentType::AND),
+            "OR Gate" => Ok(ComponentType::OR),
+            "NAND Gate" => Ok(ComponentType::NAND),
+            "NOR Gate" => Ok(ComponentType::NOR),
+            "XOR Gate" => Ok(ComponentType::XOR),
+            "XNOR Gate" => Ok(ComponentType::XNOR),
+            "NOT Gate" => Ok(ComponentType::NOT),
+            "Pin" => Ok(ComponentType::PIN),
+            &_ => Err(ParseError::UnsupportedComponent {
+                file: self.file_path.clone(),
+                name: String::from(name),
+            }),
         }
+    }
+
+    /// Transpiles the main circuit of the project.
+    pub fn transpile_to_logic_code(&mut self) -> Result<String, ParseError> {
+        let main = self.parsed_project()?.main.clone();
 
-        logic_code
+        self.transpile_circuit_to_logic_code(&main)
     }
 
-    fn track_wire_destinations(&mut self, module: ModuleType) -> Vec<String> {
-        let mut possible_destinations: Vec<String> = Vec::new();
+    pub fn transpile_circuit_to_logic_code(&mut self, name: &str) -> Result<String, ParseError> {
+        match self.parsed_project()?.circuit(name) {
+            Some(circuit) => Ok(circuit.to_logic_code()),
+            None => Err(self.unknown_circuit(name)),
+        }
+    }
 
-        match module {
-            ModuleType::Component(component) => match self.wires_map.get(&component.loc) {
-                Some(_) => {
-                    self.visited_nodes.push(component.loc);
+    /// Transpiles every circuit of the project into its own `.logic` module, keyed by the
+    /// circuit name.
+    pub fn transpile_all_to_logic_code(&mut self) -> Result<BTreeMap<String, String>, ParseError> {
+        Ok(self.parsed_project()?.to_logic_modules())
+    }
 
-                    possible_destinations =
-                        self.track_wire_destinations(ModuleType::WireDestinations(
-                            self.wires_map.get(&component.loc).unwrap().clone(),
-                        ));
-                }
-                None => {
-                    panic!("The component does not have any wires connected to it!");
-                }
-            },
+    /// Transpiles the main circuit with every subcircuit instance expanded in place.
+    pub fn transpile_flat_to_logic_code(&mut self) -> Result<String, ParseError> {
+        let project = self.parsed_project()?;
+        let flat = project.flatten(&project.main);
 
-            ModuleType::WireDestinations(routes) => {
-                for route in routes {
-                    if self.visited_nodes.contains(&route) {
-                        continue;
-                    }
+        flat.map(|circuit| circuit.to_logic_code())
+            .map_err(|source| ParseError::Flatten {
+                file: self.file_path.clone(),
+                source,
+            })
+    }
 
-                    match self.components_map.get(&route) {
-                        Some(component_id) => {
-                            possible_destinations.push(component_id.clone());
-                        }
-                        None => match self.wires_map.get(&route) {
-                            Some(_) => {
-                                self.visited_nodes.push(route);
-
-                                possible_destinations.append(&mut self.track_wire_destinations(
-                                    ModuleType::WireDestinations(
-                                        self.wires_map.get(&route).unwrap().clone(),
-                                    ),
-                                ));
-                            }
-                            None => panic!("The route does not lead to anything!"),
-                        },
-                    }
-                }
-            }
-        }
+    /// Transpiles every circuit of the project into a Verilog module, see
+    /// `Circuit::to_verilog`.
+    pub fn transpile_to_verilog(&mut self) -> Result<String, ParseError> {
+        Ok(self.parsed_project()?.to_verilog())
+    }
 
-        possible_destinations
-    }
-
-    fn parse_string_to_coordinate(&mut self, string: &str) -> Coordinate {
-        let mut coord = String::from(string).replace("(", "");
-        coord = coord.replace(")", "");
-        coord = coord.replace(" ", "");
-        let mut split = coord.split(",");
-
-        let x = split.next().unwrap().parse::<u32>().unwrap();
-        let y = split.next().unwrap().parse::<u32>().unwrap();
-
-        Coordinate { x, y }
-    }
-
-    fn calculate_input_coords(&mut self, component: &Component) -> Vec<Coordinate> {
-        let mut input_coords: Vec<Coordinate> = Vec::new();
-
-        match component.name.as_str() {
-            "AND Gate" | "OR Gate" => {
-                // the distance constant is 30
-                match component.attributes.get("facing") {
-                    Some(facing) => match facing.as_str() {
-                        "north" => match component.attributes.get("inputs") {
-                            Some(inputs) => {
-                                let inputs = inputs.parse::<u32>().unwrap();
-
-                                if inputs % 2 == 0 {
-                                    for i in 0..inputs / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y + 30,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y + 30,
-                                        });
-                                    }
-                                } else {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x,
-                                        y: component.loc.y + 30,
-                                    });
-
-                                    for i in 0..(inputs - 1) / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y + 30,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y + 30,
-                                        });
-                                    }
-                                }
-                            }
-                            None => {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x - 10,
-                                    y: component.loc.y + 30,
-                                });
-
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 10,
-                                    y: component.loc.y + 30,
-                                });
-                            }
-                        },
-
-                        "south" => match component.attributes.get("inputs") {
-                            Some(inputs) => {
-                                let inputs = inputs.parse::<u32>().unwrap();
-
-                                if inputs % 2 == 0 {
-                                    for i in 0..inputs / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y - 30,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y - 30,
-                                        });
-                                    }
-                                } else {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x,
-                                        y: component.loc.y - 30,
-                                    });
-
-                                    for i in 0..(inputs - 1) / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y - 30,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y - 30,
-                                        });
-                                    }
-                                }
-                            }
-                            None => {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x - 10,
-                                    y: component.loc.y - 30,
-                                });
-
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 10,
-                                    y: component.loc.y - 30,
-                                });
-                            }
-                        },
-
-                        "west" => match component.attributes.get("inputs") {
-                            Some(inputs) => {
-                                let inputs = inputs.parse::<u32>().unwrap();
-
-                                if inputs % 2 == 0 {
-                                    for i in 0..inputs / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 30,
-                                            y: component.loc.y - (10 * (i + 1)),
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 30,
-                                            y: component.loc.y + (10 * (i + 1)),
-                                        });
-                                    }
-                                } else {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x + 30,
-                                        y: component.loc.y,
-                                    });
-
-                                    for i in 0..(inputs - 1) / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 30,
-                                            y: component.loc.y - (10 * (i + 1)),
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 30,
-                                            y: component.loc.y + (10 * (i + 1)),
-                                        });
-                                    }
-                                }
-                            }
-                            None => {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 30,
-                                    y: component.loc.y - 10,
-                                });
-
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 30,
-                                    y: component.loc.y + 10,
-                                });
-                            }
-                        },
-
-                        &_ => todo!("Facing not implemented"),
-                    },
-                    None => match component.attributes.get("inputs") {
-                        // Default to East facing
-                        Some(inputs) => {
-                            let inputs = inputs.parse::<u32>().unwrap();
-
-                            if inputs % 2 == 0 {
-                                for i in 0..inputs / 2 {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 30,
-                                        y: component.loc.y - (10 * (i + 1)),
-                                    });
-
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 30,
-                                        y: component.loc.y + (10 * (i + 1)),
-                                    });
-                                }
-                            } else {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x - 30,
-                                    y: component.loc.y,
-                                });
-
-                                for i in 0..(inputs - 1) / 2 {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 30,
-                                        y: component.loc.y - (10 * (i + 1)),
-                                    });
-
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 30,
-                                        y: component.loc.y + (10 * (i + 1)),
-                                    });
-                                }
-                            }
-                        }
+    /// Transpiles every circuit of the project into a VHDL entity and architecture, see
+    /// `Circuit::to_vhdl`.
+    pub fn transpile_to_vhdl(&mut self) -> Result<String, ParseError> {
+        Ok(self.parsed_project()?.to_vhdl())
+    }
 
-                        None => {
-                            input_coords.push(Coordinate {
-                                x: component.loc.x - 30,
-                                y: component.loc.y - 10,
-                            });
+    /// Draws the main circuit of the project as a Graphviz graph, see `Circuit::to_dot`.
+    pub fn transpile_to_dot(&mut self) -> Result<String, ParseError> {
+        let project = self.parsed_project()?;
 
-                            input_coords.push(Coordinate {
-                                x: component.loc.x - 30,
-                                y: component.loc.y + 10,
-                            });
-                        }
-                    },
-                }
+        match project.main_circuit() {
+            Some(circuit) => Ok(circuit.to_dot()),
+            None => {
+                let main = project.main.clone();
+                Err(self.unknown_circuit(&main))
             }
+        }
+    }
 
-            "NAND Gate" | "NOR Gate" | "XOR Gate" => {
-                // the distance constant is 40
-                match component.attributes.get("facing") {
-                    Some(facing) => match facing.as_str() {
-                        "north" => match component.attributes.get("inputs") {
-                            Some(inputs) => {
-                                let inputs = inputs.parse::<u32>().unwrap();
-
-                                if inputs % 2 == 0 {
-                                    for i in 0..inputs / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y + 40,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y + 40,
-                                        });
-                                    }
-                                } else {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x,
-                                        y: component.loc.y + 40,
-                                    });
-
-                                    for i in 0..(inputs - 1) / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y + 40,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y + 40,
-                                        });
-                                    }
-                                }
-                            }
-                            None => {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x - 10,
-                                    y: component.loc.y + 40,
-                                });
-
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 10,
-                                    y: component.loc.y + 40,
-                                });
-                            }
-                        },
-
-                        "south" => match component.attributes.get("inputs") {
-                            Some(inputs) => {
-                                let inputs = inputs.parse::<u32>().unwrap();
-
-                                if inputs % 2 == 0 {
-                                    for i in 0..inputs / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y - 40,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y - 40,
-                                        });
-                                    }
-                                } else {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x,
-                                        y: component.loc.y - 40,
-                                    });
-
-                                    for i in 0..(inputs - 1) / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y - 40,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y - 40,
-                                        });
-                                    }
-                                }
-                            }
-                            None => {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x - 10,
-                                    y: component.loc.y - 40,
-                                });
-
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 10,
-                                    y: component.loc.y - 40,
-                                });
-                            }
-                        },
-
-                        "west" => match component.attributes.get("inputs") {
-                            Some(inputs) => {
-                                let inputs = inputs.parse::<u32>().unwrap();
-
-                                if inputs % 2 == 0 {
-                                    for i in 0..inputs / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 40,
-                                            y: component.loc.y - (10 * (i + 1)),
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 40,
-                                            y: component.loc.y + (10 * (i + 1)),
-                                        });
-                                    }
-                                } else {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x + 40,
-                                        y: component.loc.y,
-                                    });
-
-                                    for i in 0..(inputs - 1) / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 40,
-                                            y: component.loc.y - (10 * (i + 1)),
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 40,
-                                            y: component.loc.y + (10 * (i + 1)),
-                                        });
-                                    }
-                                }
-                            }
-                            None => {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 40,
-                                    y: component.loc.y - 10,
-                                });
-
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 40,
-                                    y: component.loc.y + 10,
-                                });
-                            }
-                        },
-
-                        &_ => todo!("Facing not implemented"),
-                    },
-
-                    None => match component.attributes.get("inputs") {
-                        // Default to East facing
-                        Some(inputs) => {
-                            let inputs = inputs.parse::<u32>().unwrap();
-
-                            if inputs % 2 == 0 {
-                                for i in 0..inputs / 2 {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 40,
-                                        y: component.loc.y - (10 * (i + 1)),
-                                    });
-
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 40,
-                                        y: component.loc.y + (10 * (i + 1)),
-                                    });
-                                }
-                            } else {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x - 40,
-                                    y: component.loc.y,
-                                });
-
-                                for i in 0..(inputs - 1) / 2 {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 40,
-                                        y: component.loc.y - (10 * (i + 1)),
-                                    });
-
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 40,
-                                        y: component.loc.y + (10 * (i + 1)),
-                                    });
-                                }
-                            }
-                        }
-
-                        None => {
-                            input_coords.push(Coordinate {
-                                x: component.loc.x - 40,
-                                y: component.loc.y - 10,
-                            });
+    /// Draws the main circuit of the project as an SVG picture, see `Circuit::to_svg`.
+    pub fn transpile_to_svg(&mut self) -> Result<String, ParseError> {
+        let project = self.parsed_project()?;
 
-                            input_coords.push(Coordinate {
-                                x: component.loc.x - 40,
-                                y: component.loc.y + 10,
-                            });
-                        }
-                    },
-                }
+        match project.main_circuit() {
+            Some(circuit) => Ok(circuit.to_svg()),
+            None => {
+                let main = project.main.clone();
+                Err(self.unknown_circuit(&main))
             }
+        }
+    }
 
-            "XNOR Gate" => {
-                // the distance constant is 50
-                match component.attributes.get("facing") {
-                    Some(facing) => match facing.as_str() {
-                        "north" => match component.attributes.get("inputs") {
-                            Some(inputs) => {
-                                let inputs = inputs.parse::<u32>().unwrap();
-
-                                if inputs % 2 == 0 {
-                                    for i in 0..inputs / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y + 50,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y + 50,
-                                        });
-                                    }
-                                } else {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x,
-                                        y: component.loc.y + 50,
-                                    });
-
-                                    for i in 0..(inputs - 1) / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y + 50,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y + 50,
-                                        });
-                                    }
-                                }
-                            }
-                            None => {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x - 10,
-                                    y: component.loc.y + 50,
-                                });
-
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 10,
-                                    y: component.loc.y + 50,
-                                });
-                            }
-                        },
-
-                        "south" => match component.attributes.get("inputs") {
-                            Some(inputs) => {
-                                let inputs = inputs.parse::<u32>().unwrap();
-
-                                if inputs % 2 == 0 {
-                                    for i in 0..inputs / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y - 50,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y - 50,
-                                        });
-                                    }
-                                } else {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x,
-                                        y: component.loc.y - 50,
-                                    });
-
-                                    for i in 0..(inputs - 1) / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x - (10 * (i + 1)),
-                                            y: component.loc.y - 50,
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + (10 * (i + 1)),
-                                            y: component.loc.y - 50,
-                                        });
-                                    }
-                                }
-                            }
-                            None => {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x - 10,
-                                    y: component.loc.y - 50,
-                                });
-
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 10,
-                                    y: component.loc.y - 50,
-                                });
-                            }
-                        },
-
-                        "west" => match component.attributes.get("inputs") {
-                            Some(inputs) => {
-                                let inputs = inputs.parse::<u32>().unwrap();
-
-                                if inputs % 2 == 0 {
-                                    for i in 0..inputs / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 50,
-                                            y: component.loc.y - (10 * (i + 1)),
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 50,
-                                            y: component.loc.y + (10 * (i + 1)),
-                                        });
-                                    }
-                                } else {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x + 50,
-                                        y: component.loc.y,
-                                    });
-
-                                    for i in 0..(inputs - 1) / 2 {
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 50,
-                                            y: component.loc.y - (10 * (i + 1)),
-                                        });
-
-                                        input_coords.push(Coordinate {
-                                            x: component.loc.x + 50,
-                                            y: component.loc.y + (10 * (i + 1)),
-                                        });
-                                    }
-                                }
-                            }
-                            None => {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 50,
-                                    y: component.loc.y - 10,
-                                });
-
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x + 50,
-                                    y: component.loc.y + 10,
-                                });
-                            }
-                        },
-
-                        &_ => todo!("Facing not implemented"),
-                    },
-
-                    None => match component.attributes.get("inputs") {
-                        // Default to East facing
-                        Some(inputs) => {
-                            let inputs = inputs.parse::<u32>().unwrap();
-
-                            if inputs % 2 == 0 {
-                                for i in 0..inputs / 2 {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 50,
-                                        y: component.loc.y - (10 * (i + 1)),
-                                    });
-
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 50,
-                                        y: component.loc.y + (10 * (i + 1)),
-                                    });
-                                }
-                            } else {
-                                input_coords.push(Coordinate {
-                                    x: component.loc.x - 50,
-                                    y: component.loc.y,
-                                });
-
-                                for i in 0..(inputs - 1) / 2 {
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 50,
-                                        y: component.loc.y - (10 * (i + 1)),
-                                    });
-
-                                    input_coords.push(Coordinate {
-                                        x: component.loc.x - 50,
-                                        y: component.loc.y + (10 * (i + 1)),
-                                    });
-                                }
-                            }
-                        }
+    fn parsed_project(&mut self) -> Result<&Project, ParseError> {
+        if self.project.is_none() {
+            self.parse_project()?;
+        }
 
-                        None => {
-                            input_coords.push(Coordinate {
-                                x: component.loc.x - 50,
-                                y: component.loc.y - 10,
-                            });
+        Ok(self.project.as_ref().expect("the project was just parsed"))
+    }
 
-                            input_coords.push(Coordinate {
-                                x: component.loc.x - 50,
-                                y: component.loc.y + 10,
-                            });
-                        }
-                    },
-                }
+    fn unknown_circuit(&self, name: &str) -> ParseError {
+        ParseError::UnknownCircuit {
+            file: self.file_path.clone(),
+            name: String::from(name),
+        }
+    }
+
+    /// Reports components that are not on any net and wire ends that touch nothing.
+    fn check_connectivity(&mut self, circuit: &Circuit) {
+        let connected: HashSet<&str> = circuit
+            .nets
+            .iter()
+            .flat_map(|net| &net.ports)
+            .map(|port| port.component.as_str())
+            .collect();
+        let mut ports: HashSet<Coordinate> = HashSet::new();
+
+        for component in &circuit.components {
+            if !connected.contains(component.id.as_str()) {
+                self.report(
+                    Severity::Warning,
+                    Some(&component.id),
+                    Some(component.loc),
+                    String::from("the component does not have any wires connected to it"),
+                );
             }
 
-            "NOT Gate" => {
-                // the distance constant is 20
-                match component.attributes.get("facing") {
-                    Some(facing) => match facing.as_str() {
-                        "north" => {
-                            input_coords.push(Coordinate {
-                                x: component.loc.x,
-                                y: component.loc.y + 20,
-                            });
-                        }
+            ports.extend(component.ports.iter().map(|port| port.loc));
+        }
 
-                        "south" => {
-                            input_coords.push(Coordinate {
-                                x: component.loc.x,
-                                y: component.loc.y - 20,
-                            });
-                        }
+        let mut dangling: Vec<Coordinate> = self
+            .wires_map
+            .iter()
+            .filter(|(coord, routes)| routes.len() == 1 && !ports.contains(coord))
+            .map(|(coord, _)| *coord)
+            .collect();
+        dangling.sort_by_key(|coord| (coord.x, coord.y));
+
+        for coord in dangling {
+            self.report(
+                Severity::Warning,
+                None,
+                Some(coord),
+                String::from("the wire end does not touch anything"),
+            );
+        }
+    }
 
-                        "west" => {
-                            input_coords.push(Coordinate {
-                                x: component.loc.x + 20,
-                                y: component.loc.y,
-                            });
-                        }
+    /// Reports nets that more than one output drives at the same time.
+    fn check_drivers(&mut self, circuit: &Circuit) {
+        for net in &circuit.nets {
+            let outputs: Vec<&Port> = net
+                .ports
+                .iter()
+                .filter(|port| port.direction == PortDirection::Output)
+                .collect();
+
+            if outputs.len() > 1 {
+                let drivers: Vec<&str> =
+                    outputs.iter().map(|port| port.component.as_str()).collect();
+
+                self.report(
+                    Severity::Error,
+                    None,
+                    net.points.first().copied(),
+                    format!("the outputs of {} are wired together", drivers.join(", ")),
+                );
+            }
+        }
+    }
 
-                        &_ => todo!("Facing not implemented"),
-                    },
+    /// Groups connected wire points into nets with a single union-find pass, then assigns every
+    /// component port to the net of the point it sits on. Ports touching each other directly
+    /// share a net even without a wire between them.
+    fn extract_nets(&self, components: &[Component], wires: &[Wire]) -> Vec<Net> {
+        let ports: Vec<&Port> = components
+            .iter()
+            .flat_map(|component| &component.ports)
+            .collect();
+
+        // Points are numbered in the order wires and then ports mention them, which is also the
+        // order nets are numbered in
+        let mut points: Vec<Coordinate> = Vec::new();
+        let mut indices: HashMap<Coordinate, usize> = HashMap::new();
+        let wire_points = wires.iter().flat_map(|wire| [wire.from, wire.to]);
+        for point in wire_points.chain(ports.iter().map(|port| port.loc)) {
+            indices.entry(point).or_insert_with(|| {
+                points.push(point);
+                points.len() - 1
+            });
+        }
 
-                    None => {
-                        // Default to East facing
-                        input_coords.push(Coordinate {
-                            x: component.loc.x - 20,
-                            y: component.loc.y,
-                        });
-                    }
-                }
+        let mut sets = UnionFind::new(points.len());
+        for (point, neighbours) in &self.wires_map {
+            for neighbour in neighbours {
+                sets.union(indices[point], indices[neighbour]);
             }
+        }
 
-            "Pin" => {
-                input_coords.push(Coordinate {
-                    x: component.loc.x,
-                    y: component.loc.y,
+        let mut nets: Vec<Net> = Vec::new();
+        let mut net_of_root: HashMap<usize, usize> = HashMap::new();
+        for (index, point) in points.iter().enumerate() {
+            let root = sets.find(index);
+            let net = *net_of_root.entry(root).or_insert_with(|| {
+                nets.push(Net {
+                    id: String::new(),
+                    points: Vec::new(),
+                    ports: Vec::new(),
                 });
-            }
+                nets.len() - 1
+            });
+
+            nets[net].points.push(*point);
+        }
+
+        for port in ports {
+            let root = sets.find(indices[&port.loc]);
+            nets[net_of_root[&root]].ports.push(port.clone());
+        }
 
-            &_ => todo!("Component not implemented"),
+        // A port alone on a point that no wire reaches is not connected to anything
+        nets.retain(|net| {
+            net.ports.len() > 1
+                || net
+                    .points
+                    .iter()
+                    .any(|point| self.wires_map.contains_key(point))
+        });
+        for (index, net) in nets.iter_mut().enumerate() {
+            net.id = format!("net_{}", index);
         }
 
-        input_coords
+        nets
     }
 
-    fn map_components_by_location(&mut self) {
-        let mut map: HashMap<Coordinate, String> = HashMap::new();
+    fn report(
+        &mut self,
+        severity: Severity,
+        component: Option<&str>,
+        location: Option<Coordinate>,
+        message: String,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            file: self.file_path.clone(),
+            circuit: self.current_circuit.clone(),
+            component: component.map(String::from),
+            location,
+            message,
+        });
+    }
 
-        for component in &self.components.clone() {
-            let all_coords = self.calculate_input_coords(&component);
+    fn get_attribute<'e>(&self, elem: &'e Element, attribute: &str) -> Result<&'e str, ParseError> {
+        elem.attributes
+            .get(attribute)
+            .map(|value| value.as_str())
+            .ok_or_else(|| ParseError::MissingAttribute {
+                file: self.file_path.clone(),
+                element: elem.name.clone(),
+                attribute: String::from(attribute),
+            })
+    }
 
-            for coord in all_coords {
-                map.insert(coord, component.id.clone());
-            }
+    fn parse_string_to_coordinate(
+        &self,
+        elem: &Element,
+        attribute: &str,
+        string: &str,
+    ) -> Result<Coordinate, ParseError> {
+        let invalid = || ParseError::InvalidCoordinate {
+            file: self.file_path.clone(),
+            element: elem.name.clone(),
+            attribute: String::from(attribute),
+            value: String::from(string),
+        };
+
+        let mut coord = String::from(string).replace('(', "");
+        coord = coord.replace(')', "");
+        coord = coord.replace(' ', "");
+        let mut split = coord.split(',');
+
+        let x = split.next().and_then(|x| x.parse::<u32>().ok());
+        let y = split.next().and_then(|y| y.parse::<u32>().ok());
+
+        match (x, y, split.next()) {
+            (Some(x), Some(y), None) => Ok(Coordinate { x, y }),
+            _ => Err(invalid()),
         }
-
-        self.components_map = map;
     }
 
-    fn map_wires_by_location(&mut self) {
-        let mut map: HashMap<Coordinate, Vec<Coordinate>> = HashMap::new();
+    /// Works out where the component's ports are. Gates have their output `out` first, followed
+    /// by their inputs `in1` to `inN` in the order Logisim numbers them (just `in` for NOT gates).
+    /// Pins have a single port, `out` on input pins and `in` on output pins.
+    fn calculate_ports(&self, component: &Component) -> Result<Vec<Port>, ParseError> {
+        let port = |index: usize, direction: PortDirection, name: &str, loc: Coordinate| Port {
+            component: component.id.clone(),
+            index,
+            direction,
+            name: String::from(name),
+            loc,
+            pin: None,
+        };
+
+        // Distance between the output (`loc`) and the input side of each gate shape
+        let depth: i64 = match &component.component_type {
+            ComponentType::PIN => {
+                let output = component
+                    .attributes
+                    .get("output")
+                    .is_some_and(|output| output == "true");
+                // Input pins feed the circuit, output pins read from it
+                let (direction, name) = if output {
+                    (PortDirection::Input, "in")
+                } else {
+                    (PortDirection::Output, "out")
+                };
+
+                return Ok(vec![port(0, direction, name, component.loc)]);
+            }
+            ComponentType::Subcircuit(name) => {
+                let appearance = self
+                    .appearances
+                    .get(name)
+                    .ok_or_else(|| self.unknown_circuit(name))?;
+                let facing = component.facing();
+
+                let mut ports: Vec<Port> = Vec::new();
+                for (index, appearance_port) in appearance.ports.iter().enumerate() {
+                    let (dx, dy) = rotate(appearance_port.offset, &appearance.facing, facing);
+                    let direction = if appearance_port.output {
+                        PortDirection::Output
+                    } else {
+                        PortDirection::Input
+                    };
 
-        for wire in &self.wires {
-            if map.contains_key(&wire.from) {
-                let mut coords = map.get(&wire.from).unwrap().clone();
-                coords.push(wire.to.clone());
+                    ports.push(Port {
+                        pin: Some(appearance_port.pin.clone()),
+                        ..port(
+                            index,
+                            direction,
+                            &appearance_port.name,
+                            self.offset_coordinate(component, dx, dy)?,
+                        )
+                    });
+                }
 
-                map.insert(wire.from.clone(), coords);
-            } else {
-                map.insert(wire.from.clone(), vec![wire.to.clone()]);
+                return Ok(ports);
             }
+            gate => gate.depth().expect("every gate has a shape"),
+        };
+
+        let inputs: i64 = match component.component_type {
+            ComponentType::NOT => 1,
+            _ => match component.attributes.get("inputs") {
+                Some(inputs) => inputs
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|inputs| *inputs > 0)
+                    .ok_or_else(|| self.invalid_attribute("inputs", inputs))?,
+                None => 2,
+            },
+        };
+
+        // Offsets of the inputs along the input side of an east-facing gate, top to bottom,
+        // 10 units apart and skipping the middle when the count is even
+        let spread: Vec<i64> = (0..inputs)
+            .map(|i| 10 * (i - inputs / 2))
+            .map(|offset| {
+                if inputs % 2 == 0 && offset >= 0 {
+                    offset + 10
+                } else {
+                    offset
+                }
+            })
+            .collect();
+
+        let mut ports = vec![port(0, PortDirection::Output, "out", component.loc)];
+        for (index, offset) in spread.into_iter().enumerate() {
+            // The east-facing layout turned around the output
+            let (dx, dy) = match component.facing() {
+                "east" => (-depth, offset),
+                "west" => (depth, -offset),
+                "north" => (offset, depth),
+                "south" => (-offset, -depth),
+                facing => return Err(self.invalid_attribute("facing", facing)),
+            };
+
+            let name = match component.component_type {
+                ComponentType::NOT => String::from("in"),
+                _ => format!("in{}", index + 1),
+            };
+
+            ports.push(port(
+                index + 1,
+                PortDirection::Input,
+                &name,
+                self.offset_coordinate(component, dx, dy)?,
+            ));
+        }
 
-            if map.contains_key(&wire.to) {
-                let mut coords = map.get(&wire.to).unwrap().clone();
-                coords.push(wire.from.clone());
+        Ok(ports)
+    }
 
-                map.insert(wire.to.clone(), coords);
-            } else {
-                map.insert(wire.to.clone(), vec![wire.from.clone()]);
-            }
+    fn offset_coordinate(
+        &self,
+        component: &Component,
+        dx: i64,
+        dy: i64,
+    ) -> Result<Coordinate, ParseError> {
+        let x = u32::try_from(component.loc.x as i64 + dx);
+        let y = u32::try_from(component.loc.y as i64 + dy);
+
+        match (x, y) {
+            (Ok(x), Ok(y)) => Ok(Coordinate { x, y }),
+            _ => Err(ParseError::InvalidCoordinate {
+                file: self.file_path.clone(),
+                element: String::from("comp"),
+                attribute: String::from("loc"),
+                value: format!("({},{})", component.loc.x, component.loc.y),
+            }),
         }
+    }
 
-        self.wires_map = map;
+    fn invalid_attribute(&self, attribute: &str, value: &str) -> ParseError {
+        ParseError::InvalidAttribute {
+            file: self.file_path.clone(),
+            element: String::from("comp"),
+            attribute: String::from(attribute),
+            value: String::from(value),
+        }
+    }
+}
+
+impl FromStr for CircParser {
+    type Err = Infallible;
+
+    /// Creates a parser over the text of a `.circ` file, e.g. one embedded with `include_str!`.
+    fn from_str(circ: &str) -> Result<CircParser, Infallible> {
+        Ok(CircParser::from_bytes(circ.as_bytes()))
+    }
+}
+
+fn is_known_attribute(component_type: &ComponentType, attribute: &str) -> bool {
+    const LABEL_ATTRIBUTES: [&str; 4] = ["label", "labelfont", "labelcolor", "labelloc"];
+
+    // `delay` is not Logisim's, but sets the component's delay in simulation
+    if LABEL_ATTRIBUTES.contains(&attribute) || ["facing", "width", "delay"].contains(&attribute) {
+        return true;
+    }
+
+    match component_type {
+        ComponentType::PIN => ["output", "tristate", "pull", "radix"].contains(&attribute),
+        ComponentType::NOT => ["size", "out"].contains(&attribute),
+        ComponentType::Subcircuit(_) => false,
+        _ => {
+            ["size", "inputs", "out", "xor"].contains(&attribute)
+                || attribute
+                    .strip_prefix("negate")
+                    .is_some_and(|index| index.parse::<u32>().is_ok())
+        }
     }
 }
@@ -0,0 +1,347 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use crate::types::{Component, ComponentType, Coordinate, Port, Wire};
+
+/// A group of wire points that are electrically connected, together with the component ports
+/// that touch them.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Net {
+    pub id: String,
+    pub points: Vec<Coordinate>,
+    pub ports: Vec<Port>,
+}
+
+/// A signal flowing from the output of one component into another.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct Connection {
+    pub from: String,
+    /// Name of the port on `from`, `None` for connections that do not say
+    pub from_port: Option<String>,
+    pub to: String,
+    /// Name of the port on `to`, `None` for connections that do not say
+    pub to_port: Option<String>,
+}
+
+impl Connection {
+    /// The `from` end as written in `.logic` code: `comp_0.out`, or just `comp_0`.
+    pub fn source(&self) -> String {
+        endpoint(&self.from, self.from_port.as_deref())
+    }
+
+    /// The `to` end as written in `.logic` code: `comp_4.in1`, or just `comp_4`.
+    pub fn target(&self) -> String {
+        endpoint(&self.to, self.to_port.as_deref())
+    }
+}
+
+fn endpoint(component: &str, port: Option<&str>) -> String {
+    match port {
+        Some(port) => format!("{}.{}", component, port),
+        None => String::from(component),
+    }
+}
+
+/// The netlist of a single Logisim circuit.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Circuit {
+    pub name: String,
+    pub components: Vec<Component>,
+    pub wires: Vec<Wire>,
+    pub nets: Vec<Net>,
+    pub connections: Vec<Connection>,
+    index: Index,
+}
+
+/// Lookups over `components` and `connections`, kept up to date by the methods of `Circuit`.
+/// The vectors can still be changed directly, so nothing found here is trusted without a check.
+#[derive(Default, Clone)]
+struct Index {
+    components: HashMap<String, usize>,
+    connections: HashSet<Connection>,
+}
+
+impl Index {
+    fn components(components: &[Component]) -> HashMap<String, usize> {
+        let mut index = HashMap::new();
+        for (position, component) in components.iter().enumerate() {
+            index.entry(component.id.clone()).or_insert(position);
+        }
+        index
+    }
+}
+
+// Two circuits are the same whatever they were built with
+impl PartialEq for Index {
+    fn eq(&self, _: &Index) -> bool {
+        true
+    }
+}
+
+impl Eq for Index {}
+
+impl fmt::Debug for Index {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Index").finish_non_exhaustive()
+    }
+}
+
+impl Circuit {
+    pub fn new(name: &str) -> Circuit {
+        Circuit {
+            name: String::from(name),
+            components: Vec::new(),
+            wires: Vec::new(),
+            nets: Vec::new(),
+            connections: Vec::new(),
+            index: Index::default(),
+        }
+    }
+
+    pub fn component(&self, id: &str) -> Option<&Component> {
+        let position = self.position(id)?;
+        Some(&self.components[position])
+    }
+
+    pub fn component_mut(&mut self, id: &str) -> Option<&mut Component> {
+        let position = self.position(id)?;
+        Some(&mut self.components[position])
+    }
+
+    /// Where the component is in `components`, looked up in the index first and searched for
+    /// when the vector was changed behind its back.
+    fn position(&self, id: &str) -> Option<usize> {
+        match self.index.components.get(id) {
+            Some(&position)
+                if self
+                    .components
+                    .get(position)
+                    .is_some_and(|component| component.id == id) =>
+            {
+                Some(position)
+            }
+            _ => self
+                .components
+                .iter()
+                .position(|component| component.id == id),
+        }
+    }
+
+    pub fn add_component(&mut self, component: Component) {
+        self.index
+            .components
+            .entry(component.id.clone())
+            .or_insert(self.components.len());
+        self.components.push(component);
+    }
+
+    /// Removes a component along with its ports and every connection to or from it.
+    pub fn remove_component(&mut self, id: &str) -> Option<Component> {
+        let position = self.position(id)?;
+
+        for net in &mut self.nets {
+            net.ports.retain(|port| port.component != id);
+        }
+        self.connections
+            .retain(|connection| connection.from != id && connection.to != id);
+
+        let component = self.components.remove(position);
+        self.index.components = Index::components(&self.components);
+        self.index.connections = self.connections.iter().cloned().collect();
+
+        Some(component)
+    }
+
+    /// Connects two components without saying which of their ports are involved.
+    pub fn connect(&mut self, from: &str, to: &str) {
+        self.add_connection(Connection {
+            from: String::from(from),
+            from_port: None,
+            to: String::from(to),
+            to_port: None,
+        });
+    }
+
+    /// Connects the `from_port` output of `from` to the `to_port` input of `to`.
+    pub fn connect_ports(&mut self, from: &str, from_port: &str, to: &str, to_port: &str) {
+        self.add_connection(Connection {
+            from: String::from(from),
+            from_port: Some(String::from(from_port)),
+            to: String::from(to),
+            to_port: Some(String::from(to_port)),
+        });
+    }
+
+    /// Adds `connection` unless the circuit already has it.
+    pub fn add_connection(&mut self, connection: Connection) {
+        // Connections pushed or replaced directly are indexed before looking them up
+        if self.index.connections.len() != self.connections.len() {
+            self.index.connections = self.connections.iter().cloned().collect();
+        }
+
+        if self.index.connections.insert(connection.clone()) {
+            self.connections.push(connection);
+        }
+    }
+
+    pub fn connections_from<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Connection> {
+        self.connections
+            .iter()
+            .filter(move |connection| connection.from == id)
+    }
+
+    pub fn connections_to<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Connection> {
+        self.connections
+            .iter()
+            .filter(move |connection| connection.to == id)
+    }
+
+    pub fn net_at(&self, point: Coordinate) -> Option<&Net> {
+        self.nets.iter().find(|net| net.points.contains(&point))
+    }
+
+    /// Nets that one of the component's ports touches.
+    pub fn nets_of<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Net> {
+        self.nets
+            .iter()
+            .filter(move |net| net.ports.iter().any(|port| port.component == id))
+    }
+
+    /// Links wire points to their neighbours. Like in Logisim, a wire end or a component port
+    /// lying in the middle of another wire forms a junction, so segments are split there.
+    pub(crate) fn wire_graph(&self) -> HashMap<Coordinate, Vec<Coordinate>> {
+        let mut graph: HashMap<Coordinate, Vec<Coordinate>> = HashMap::new();
+
+        // The same attachment points, ordered along rows and along columns
+        let mut by_row: BTreeSet<(u32, u32)> = BTreeSet::new();
+        let mut by_column: BTreeSet<(u32, u32)> = BTreeSet::new();
+        let mut add_point = |point: Coordinate| {
+            by_row.insert((point.y, point.x));
+            by_column.insert((point.x, point.y));
+        };
+
+        for wire in &self.wires {
+            add_point(wire.from);
+            add_point(wire.to);
+        }
+        for port in self
+            .components
+            .iter()
+            .flat_map(|component| &component.ports)
+        {
+            add_point(port.loc);
+        }
+
+        for wire in &self.wires {
+            let (from, to) = (wire.from, wire.to);
+
+            // Logisim only draws horizontal and vertical wires; anything else is left whole
+            let mut stops: Vec<Coordinate> = if from.y == to.y {
+                let (start, end) = (from.x.min(to.x), from.x.max(to.x));
+                by_row
+                    .range((from.y, start)..=(from.y, end))
+                    .map(|&(y, x)| Coordinate { x, y })
+                    .collect()
+            } else if from.x == to.x {
+                let (start, end) = (from.y.min(to.y), from.y.max(to.y));
+                by_column
+                    .range((from.x, start)..=(from.x, end))
+                    .map(|&(x, y)| Coordinate { x, y })
+                    .collect()
+            } else {
+                vec![from, to]
+            };
+            stops.dedup();
+
+            for pair in stops.windows(2) {
+                graph.entry(pair[0]).or_default().push(pair[1]);
+                graph.entry(pair[1]).or_default().push(pair[0]);
+            }
+        }
+
+        graph
+    }
+
+    /// Rebuilds `connections` from `nets`: the component driving a net is connected to every
+    /// other component reading from it.
+    pub fn derive_connections(&mut self) {
+        // The ports of every component that drive a net, with that net, in net order
+        let mut drivers: HashMap<&str, Vec<(&Port, &Net)>> = HashMap::new();
+        for net in &self.nets {
+            for port in net.ports.iter().filter(|port| port.drives()) {
+                drivers
+                    .entry(port.component.as_str())
+                    .or_default()
+                    .push((port, net));
+            }
+        }
+
+        let mut connections: Vec<Connection> = Vec::new();
+        for component in &self.components {
+            let Some(driving) = drivers.get(component.id.as_str()) else {
+                continue;
+            };
+
+            for (driver, net) in driving {
+                for sink in net.ports.iter().filter(|port| port.reads()) {
+                    if sink.component == driver.component && sink.index == driver.index {
+                        continue;
+                    }
+
+                    connections.push(Connection {
+                        from: component.id.clone(),
+                        from_port: Some(driver.name.clone()),
+                        to: sink.component.clone(),
+                        to_port: Some(sink.name.clone()),
+                    });
+                }
+            }
+        }
+
+        self.index.connections = connections.iter().cloned().collect();
+        self.connections = connections;
+    }
+
+    pub fn to_logic_code(&self) -> String {
+        let mut logic_code = String::new();
+
+        // DEFINE COMPONENTS
+        for component in &self.components {
+            // Syntax:
+            // #define <id>
+            // - type: <type>
+            // - circuit: <name>       (subcircuit instances only)
+            // - <attribute>: <value>
+            // - <attribute>: <value>
+            // ...
+
+            // #attach <id>.<port> <id>.<port>
+            // #attach <id>.<port> <id>.<port>
+            // ...
+            //
+            // The ports can be left out (`#attach <id> <id>`) when they are not known
+
+            logic_code.push_str(format!("#define {}\n", component.id).as_str());
+            logic_code.push_str(format!("- type: {:?}\n", component.component_type).as_str());
+
+            if let ComponentType::Subcircuit(circuit) = &component.component_type {
+                logic_code.push_str(format!("- circuit: {}\n", circuit).as_str());
+            }
+
+            for (attribute, value) in &component.attributes {
+                logic_code.push_str(format!("- {}: {}\n", attribute, value).as_str());
+            }
+
+            logic_code.push('\n');
+        }
+
+        // CONNECT COMPONENTS
+        for connection in &self.connections {
+            logic_code.push_str(
+                format!("#attach {} {}\n", connection.source(), connection.target()).as_str(),
+            );
+        }
+
+        logic_code
+    }
+}
@@ -0,0 +1,119 @@
+use std::{fmt, path::PathBuf};
+
+use crate::types::Coordinate;
+
+#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone, Copy)]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Note => write!(f, "note"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found while parsing or transpiling that does not stop the output from being
+/// produced.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: Option<PathBuf>,
+    pub circuit: Option<String>,
+    pub component: Option<String>,
+    pub location: Option<Coordinate>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}: {}: ", file.display(), self.severity)?,
+            None => write!(f, "<input>: {}: ", self.severity)?,
+        }
+
+        match (&self.circuit, &self.component) {
+            (Some(circuit), Some(component)) => write!(f, "{}/{}: ", circuit, component)?,
+            (Some(circuit), None) => write!(f, "{}: ", circuit)?,
+            (None, Some(component)) => write!(f, "{}: ", component)?,
+            (None, None) => (),
+        }
+
+        write!(f, "{}", self.message)?;
+
+        if let Some(location) = &self.location {
+            write!(f, " at ({},{})", location.x, location.y)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What a parser produced, along with the problems it found on the way.
+#[derive(Debug, Clone)]
+pub struct Parsed<T> {
+    pub value: T,
+    pub diagnostics: Diagnostics,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Diagnostics {
+        Diagnostics { items: Vec::new() }
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.items.push(diagnostic);
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.items.iter()
+    }
+
+    pub fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &Diagnostic> {
+        self.items
+            .iter()
+            .filter(move |diagnostic| diagnostic.severity == severity)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.with_severity(Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.items
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
@@ -0,0 +1,132 @@
+use crate::circuit::Circuit;
+use crate::netlist::{is_input_pin, is_output_pin};
+use crate::types::{Component, ComponentType, Port};
+
+impl Circuit {
+    /// A Graphviz DOT graph of the circuit, flowing left to right: a node per component, shaped
+    /// and labelled by its type and label, and an edge per connection. Input pins share the
+    /// leftmost rank and output pins the rightmost.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        dot.push_str(format!("digraph {} {{\n", quote(&self.name)).as_str());
+        dot.push_str("    rankdir=LR;\n\n");
+
+        for component in &self.components {
+            dot.push_str(
+                format!(
+                    "    {} [{}];\n",
+                    quote(&component.id),
+                    node_attributes(component)
+                )
+                .as_str(),
+            );
+        }
+
+        dot.push_str(rank(self, "source", is_input_pin).as_str());
+        dot.push_str(rank(self, "sink", is_output_pin).as_str());
+
+        if !self.connections.is_empty() {
+            dot.push('\n');
+        }
+        for connection in &self.connections {
+            // Port names only help where there is more than one to choose from
+            let mut attributes: Vec<String> = Vec::new();
+            if let (Some(port), Some(from)) =
+                (&connection.from_port, self.component(&connection.from))
+            {
+                if has_several(from, |port| port.drives()) {
+                    attributes.push(format!("taillabel={}", quote(port)));
+                }
+            }
+            if let (Some(port), Some(to)) = (&connection.to_port, self.component(&connection.to)) {
+                if has_several(to, |port| port.reads()) {
+                    attributes.push(format!("headlabel={}", quote(port)));
+                }
+            }
+
+            let attributes = if attributes.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attributes.join(", "))
+            };
+            dot.push_str(
+                format!(
+                    "    {} -> {}{};\n",
+                    quote(&connection.from),
+                    quote(&connection.to),
+                    attributes
+                )
+                .as_str(),
+            );
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// A subgraph putting every pin of a kind on the same rank, if there are any.
+fn rank(circuit: &Circuit, rank: &str, is_pin: fn(&Component) -> bool) -> String {
+    let pins: Vec<String> = circuit
+        .components
+        .iter()
+        .filter(|component| is_pin(component))
+        .map(|component| format!("{};", quote(&component.id)))
+        .collect();
+
+    if pins.is_empty() {
+        String::new()
+    } else {
+        format!("    {{ rank={}; {} }}\n", rank, pins.join(" "))
+    }
+}
+
+/// Pins look like Logisim draws them, square for inputs and round for outputs.
+fn node_attributes(component: &Component) -> String {
+    let label = component
+        .attributes
+        .get("label")
+        .filter(|label| !label.is_empty());
+
+    let (label, shape) = match &component.component_type {
+        ComponentType::PIN => (
+            label.unwrap_or(&component.id).clone(),
+            if is_output_pin(component) {
+                "circle"
+            } else {
+                "square"
+            },
+        ),
+        ComponentType::Subcircuit(circuit) => match label {
+            Some(label) => (format!("{}\n{}", circuit, label), "component"),
+            None => (circuit.clone(), "component"),
+        },
+        gate => match label {
+            Some(label) => (format!("{:?}\n{}", gate, label), "box"),
+            None => (format!("{:?}", gate), "box"),
+        },
+    };
+
+    let style = if shape == "box" {
+        ", style=rounded"
+    } else {
+        ""
+    };
+    format!("label={}, shape={}{}", quote(&label), shape, style)
+}
+
+/// Whether `component` has more than one port of a kind; subcircuits read from `.logic` code
+/// do not list their ports, so they are assumed to.
+fn has_several(component: &Component, is_port: fn(&Port) -> bool) -> bool {
+    matches!(component.component_type, ComponentType::Subcircuit(_))
+        || component.ports.iter().filter(|port| is_port(port)).count() > 1
+}
+
+fn quote(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
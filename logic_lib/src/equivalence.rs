@@ -0,0 +1,358 @@
+use std::fmt;
+
+use crate::appearance::port_name;
+use crate::bdd::Bdd;
+use crate::circuit::Circuit;
+use crate::error::EquivalenceError;
+use crate::netlist::{
+    exactly_one, is_input_pin, is_negated, is_odd_parity, is_output_pin, Netlist,
+};
+use crate::project::Project;
+use crate::simulator::Value;
+use crate::truth_table::TruthTable;
+use crate::types::{Component, ComponentType};
+
+/// Inputs up to which `Circuit::check_equivalence` simulates every combination rather than
+/// comparing BDDs.
+pub const EXHAUSTIVE_MAX_INPUTS: usize = 12;
+
+/// How two circuits are compared.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Method {
+    /// Simulate both circuits for every combination of inputs, comparing `X` and `Z` too, so
+    /// it also works for circuits with feedback such as latches
+    Exhaustive,
+    /// Compare binary decision diagrams of the outputs, which scales to many more inputs but
+    /// needs circuits without feedback whose every signal is driven by exactly one output
+    Bdd,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Equivalence {
+    Equivalent,
+    Different(Counterexample),
+}
+
+/// Input values for which an output of the submission differs from the reference.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Counterexample {
+    /// Every input pin with its value, in the order of the reference
+    pub inputs: Vec<(String, bool)>,
+    /// The first output that differs
+    pub output: String,
+    pub reference: Value,
+    pub submission: Value,
+}
+
+impl Equivalence {
+    pub fn is_equivalent(&self) -> bool {
+        *self == Equivalence::Equivalent
+    }
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inputs: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|(pin, value)| format!("{}={}", pin, Value::from(*value)))
+            .collect();
+
+        write!(
+            f,
+            "with {}, {} is {} instead of {}",
+            inputs.join(" "),
+            self.output,
+            self.submission,
+            self.reference
+        )
+    }
+}
+
+impl Circuit {
+    /// Whether `submission` computes the same outputs as this circuit from the same inputs,
+    /// matching pins by name, see `Simulator::input_names`. Circuits with up to
+    /// `EXHAUSTIVE_MAX_INPUTS` inputs are simulated exhaustively, larger ones compared with BDDs.
+    pub fn check_equivalence(&self, submission: &Circuit) -> Result<Equivalence, EquivalenceError> {
+        let inputs = Netlist::new(self)?
+            .components
+            .iter()
+            .filter(|component| is_input_pin(component))
+            .count();
+        let method = if inputs <= EXHAUSTIVE_MAX_INPUTS {
+            Method::Exhaustive
+        } else {
+            Method::Bdd
+        };
+
+        self.check_equivalence_with(submission, method)
+    }
+
+    pub fn check_equivalence_with(
+        &self,
+        submission: &Circuit,
+        method: Method,
+    ) -> Result<Equivalence, EquivalenceError> {
+        let reference_netlist = Netlist::new(self)?;
+        let submission_netlist = Netlist::new(submission)?;
+
+        let inputs = pin_names(&reference_netlist, is_input_pin);
+        let outputs = pin_names(&reference_netlist, is_output_pin);
+        let submission_inputs = pin_names(&submission_netlist, is_input_pin);
+        let submission_outputs = pin_names(&submission_netlist, is_output_pin);
+
+        if !same_names(&inputs, &submission_inputs) {
+            return Err(EquivalenceError::InputsDiffer {
+                reference: inputs,
+                submission: submission_inputs,
+            });
+        }
+        if !same_names(&outputs, &submission_outputs) {
+            return Err(EquivalenceError::OutputsDiffer {
+                reference: outputs,
+                submission: submission_outputs,
+            });
+        }
+
+        match method {
+            Method::Exhaustive => exhaustive(self, submission, inputs.len()),
+            Method::Bdd => {
+                let mut bdd = Bdd::new(inputs.len());
+                let reference = outputs_of(&reference_netlist, &inputs, &mut bdd)?;
+                let submitted = outputs_of(&submission_netlist, &inputs, &mut bdd)?;
+
+                for (output, f) in &reference {
+                    let g = submitted
+                        .iter()
+                        .find(|(name, _)| name == output)
+                        .map(|(_, g)| *g)
+                        .expect("outputs were matched by name");
+
+                    let difference = bdd.xor(*f, g);
+                    if let Some(values) = bdd.satisfy(difference) {
+                        return Ok(Equivalence::Different(Counterexample {
+                            inputs: inputs.iter().cloned().zip(values.iter().copied()).collect(),
+                            output: output.clone(),
+                            reference: Value::from(bdd.evaluate(*f, &values)),
+                            submission: Value::from(bdd.evaluate(g, &values)),
+                        }));
+                    }
+                }
+
+                Ok(Equivalence::Equivalent)
+            }
+        }
+    }
+}
+
+impl Project {
+    /// Flattens the main circuits of both projects and checks them with
+    /// `Circuit::check_equivalence`.
+    pub fn check_equivalence(&self, submission: &Project) -> Result<Equivalence, EquivalenceError> {
+        let reference = self.flatten(&self.main)?;
+        let submission = submission.flatten(&submission.main)?;
+
+        reference.check_equivalence(&submission)
+    }
+}
+
+fn exhaustive(
+    reference: &Circuit,
+    submission: &Circuit,
+    inputs: usize,
+) -> Result<Equivalence, EquivalenceError> {
+    let reference_table = TruthTable::with_max_inputs(reference, inputs)?;
+    let submission_table = TruthTable::with_max_inputs(submission, inputs)?;
+
+    // Where each reference input and output is in the submission table
+    let input_order: Vec<usize> = submission_table
+        .inputs
+        .iter()
+        .map(|input| position(&reference_table.inputs, input))
+        .collect();
+    let output_order: Vec<usize> = reference_table
+        .outputs
+        .iter()
+        .map(|output| position(&submission_table.outputs, output))
+        .collect();
+
+    for row in &reference_table.rows {
+        let inputs: Vec<bool> = input_order.iter().map(|input| row.inputs[*input]).collect();
+        let submitted = submission_table
+            .lookup(&inputs)
+            .expect("truth tables have every row");
+
+        for (output, value) in row.outputs.iter().enumerate() {
+            if submitted[output_order[output]] != *value {
+                return Ok(Equivalence::Different(Counterexample {
+                    inputs: reference_table
+                        .inputs
+                        .iter()
+                        .cloned()
+                        .zip(row.inputs.iter().copied())
+                        .collect(),
+                    output: reference_table.outputs[output].clone(),
+                    reference: *value,
+                    submission: submitted[output_order[output]],
+                }));
+            }
+        }
+    }
+
+    Ok(Equivalence::Equivalent)
+}
+
+fn position(names: &[String], name: &str) -> usize {
+    names
+        .iter()
+        .position(|known| known == name)
+        .expect("pins were matched by name")
+}
+
+fn pin_names(netlist: &Netlist, is_pin: fn(&Component) -> bool) -> Vec<String> {
+    netlist
+        .components
+        .iter()
+        .filter(|component| is_pin(component))
+        .map(|component| port_name(&netlist.components, component))
+        .collect()
+}
+
+fn same_names(reference: &[String], submission: &[String]) -> bool {
+    let mut reference = reference.to_vec();
+    let mut submission = submission.to_vec();
+    reference.sort();
+    submission.sort();
+
+    reference == submission
+}
+
+/// The BDD of every output pin, with input pins as variables in the order of `inputs`.
+fn outputs_of(
+    netlist: &Netlist,
+    inputs: &[String],
+    bdd: &mut Bdd,
+) -> Result<Vec<(String, usize)>, EquivalenceError> {
+    let mut builder = Builder {
+        netlist,
+        inputs,
+        functions: vec![None; netlist.components.len()],
+        visiting: vec![false; netlist.components.len()],
+    };
+
+    let mut outputs: Vec<(String, usize)> = Vec::new();
+    for (index, component) in netlist.components.iter().enumerate() {
+        if is_output_pin(component) {
+            outputs.push((
+                port_name(&netlist.components, component),
+                builder.function(index, bdd)?,
+            ));
+        }
+    }
+
+    Ok(outputs)
+}
+
+struct Builder<'a> {
+    netlist: &'a Netlist,
+    inputs: &'a [String],
+    functions: Vec<Option<usize>>,
+    visiting: Vec<bool>,
+}
+
+impl Builder<'_> {
+    fn function(&mut self, index: usize, bdd: &mut Bdd) -> Result<usize, EquivalenceError> {
+        if let Some(function) = self.functions[index] {
+            return Ok(function);
+        }
+
+        let component = &self.netlist.components[index];
+        if self.visiting[index] {
+            return Err(EquivalenceError::Sequential(component.id.clone()));
+        }
+
+        if is_input_pin(component) {
+            let variable = position(self.inputs, &port_name(&self.netlist.components, component));
+            let function = bdd.variable(variable);
+            self.functions[index] = Some(function);
+            return Ok(function);
+        }
+
+        self.visiting[index] = true;
+        let mut operands: Vec<usize> = Vec::new();
+        for (input, drivers) in self.netlist.inputs[index].iter().enumerate() {
+            match drivers.as_slice() {
+                // Left out, as Logisim does by default
+                [] => {}
+                [driver] => {
+                    let operand = self.function(*driver, bdd)?;
+                    operands.push(if is_negated(component, input) {
+                        bdd.not(operand)
+                    } else {
+                        operand
+                    });
+                }
+                _ => return Err(EquivalenceError::Undefined(component.id.clone())),
+            }
+        }
+        self.visiting[index] = false;
+
+        if operands.is_empty() {
+            return Err(EquivalenceError::Undefined(component.id.clone()));
+        }
+
+        let fold = |bdd: &mut Bdd, operation: fn(&mut Bdd, usize, usize) -> usize| {
+            operands[1..]
+                .iter()
+                .fold(operands[0], |f, operand| operation(bdd, f, *operand))
+        };
+        let function = match component.component_type {
+            ComponentType::PIN => operands[0],
+            ComponentType::NOT => bdd.not(operands[0]),
+            ComponentType::AND => fold(bdd, Bdd::and),
+            ComponentType::NAND => {
+                let and = fold(bdd, Bdd::and);
+                bdd.not(and)
+            }
+            ComponentType::OR => fold(bdd, Bdd::or),
+            ComponentType::NOR => {
+                let or = fold(bdd, Bdd::or);
+                bdd.not(or)
+            }
+            ComponentType::XOR if is_odd_parity(component) => fold(bdd, Bdd::xor),
+            ComponentType::XOR => one_high(bdd, &operands),
+            ComponentType::XNOR if is_odd_parity(component) => {
+                let xor = fold(bdd, Bdd::xor);
+                bdd.not(xor)
+            }
+            ComponentType::XNOR => {
+                let one = one_high(bdd, &operands);
+                bdd.not(one)
+            }
+            ComponentType::Subcircuit(_) => unreachable!("subcircuits are rejected by `Netlist`"),
+        };
+
+        self.functions[index] = Some(function);
+        Ok(function)
+    }
+}
+
+/// Logisim's default XOR over `operands`.
+fn one_high(bdd: &mut Bdd, operands: &[usize]) -> usize {
+    let mut sum = Bdd::FALSE;
+
+    for term in exactly_one(operands.len()) {
+        let mut product = Bdd::TRUE;
+        for (input, complemented) in term {
+            let literal = if complemented {
+                bdd.not(operands[input])
+            } else {
+                operands[input]
+            };
+            product = bdd.and(product, literal);
+        }
+        sum = bdd.or(sum, product);
+    }
+
+    sum
+}
@@ -0,0 +1,542 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::simulator::Value;
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io {
+        file: Option<PathBuf>,
+        source: std::io::Error,
+    },
+    Xml {
+        file: Option<PathBuf>,
+        source: xmltree::ParseError,
+    },
+    MissingElement {
+        file: Option<PathBuf>,
+        element: String,
+    },
+    MissingAttribute {
+        file: Option<PathBuf>,
+        element: String,
+        attribute: String,
+    },
+    InvalidAttribute {
+        file: Option<PathBuf>,
+        element: String,
+        attribute: String,
+        value: String,
+    },
+    InvalidCoordinate {
+        file: Option<PathBuf>,
+        element: String,
+        attribute: String,
+        value: String,
+    },
+    UnsupportedComponent {
+        file: Option<PathBuf>,
+        name: String,
+    },
+    UnknownCircuit {
+        file: Option<PathBuf>,
+        name: String,
+    },
+    Flatten {
+        file: Option<PathBuf>,
+        source: FlattenError,
+    },
+}
+
+impl ParseError {
+    pub fn file(&self) -> Option<&Path> {
+        match self {
+            ParseError::Io { file, .. }
+            | ParseError::Xml { file, .. }
+            | ParseError::MissingElement { file, .. }
+            | ParseError::MissingAttribute { file, .. }
+            | ParseError::InvalidAttribute { file, .. }
+            | ParseError::InvalidCoordinate { file, .. }
+            | ParseError::UnsupportedComponent { file, .. }
+            | ParseError::UnknownCircuit { file, .. }
+            | ParseError::Flatten { file, .. } => file.as_deref(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.file() {
+            Some(file) => write!(f, "{}: ", file.display())?,
+            None => write!(f, "<input>: ")?,
+        }
+
+        match self {
+            ParseError::Io { source, .. } => write!(f, "failed to read file: {}", source),
+            ParseError::Xml { source, .. } => write!(f, "failed to parse XML: {}", source),
+            ParseError::MissingElement { element, .. } => {
+                write!(f, "missing <{}> element", element)
+            }
+            ParseError::MissingAttribute {
+                element, attribute, ..
+            } => write!(f, "<{}> is missing the `{}` attribute", element, attribute),
+            ParseError::InvalidAttribute {
+                element,
+                attribute,
+                value,
+                ..
+            } => write!(
+                f,
+                "<{}> has an invalid `{}` attribute: {:?}",
+                element, attribute, value
+            ),
+            ParseError::InvalidCoordinate {
+                element,
+                attribute,
+                value,
+                ..
+            } => write!(
+                f,
+                "<{}> has an invalid coordinate in `{}`: {:?}",
+                element, attribute, value
+            ),
+            ParseError::UnsupportedComponent { name, .. } => {
+                write!(f, "unsupported component {:?}", name)
+            }
+            ParseError::UnknownCircuit { name, .. } => {
+                write!(f, "no circuit named {:?} in the project", name)
+            }
+            ParseError::Flatten { source, .. } => write!(f, "failed to flatten: {}", source),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io { source, .. } => Some(source),
+            ParseError::Xml { source, .. } => Some(source),
+            ParseError::Flatten { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum FlattenError {
+    UnknownCircuit(String),
+    /// A circuit that ends up containing itself, from the outermost circuit inwards
+    RecursiveCircuit(Vec<String>),
+}
+
+impl fmt::Display for FlattenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FlattenError::UnknownCircuit(name) => {
+                write!(f, "no circuit named {:?} in the project", name)
+            }
+            FlattenError::RecursiveCircuit(cycle) => {
+                write!(f, "circuit contains itself: {}", cycle.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for FlattenError {}
+
+/// An error in `.logic` code, located by 1-based line and column.
+#[derive(Debug)]
+pub enum LogicParseError {
+    Io {
+        file: Option<PathBuf>,
+        source: std::io::Error,
+    },
+    Syntax {
+        file: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    UnknownType {
+        file: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        name: String,
+    },
+    DuplicateComponent {
+        file: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        id: String,
+    },
+    UnknownComponent {
+        file: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        id: String,
+    },
+    UnknownPort {
+        file: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        component: String,
+        port: String,
+    },
+}
+
+impl LogicParseError {
+    pub fn file(&self) -> Option<&Path> {
+        match self {
+            LogicParseError::Io { file, .. }
+            | LogicParseError::Syntax { file, .. }
+            | LogicParseError::UnknownType { file, .. }
+            | LogicParseError::DuplicateComponent { file, .. }
+            | LogicParseError::UnknownComponent { file, .. }
+            | LogicParseError::UnknownPort { file, .. } => file.as_deref(),
+        }
+    }
+
+    /// Line and column the error points at, if it comes from the code itself.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            LogicParseError::Io { .. } => None,
+            LogicParseError::Syntax { line, column, .. }
+            | LogicParseError::UnknownType { line, column, .. }
+            | LogicParseError::DuplicateComponent { line, column, .. }
+            | LogicParseError::UnknownComponent { line, column, .. }
+            | LogicParseError::UnknownPort { line, column, .. } => Some((*line, *column)),
+        }
+    }
+}
+
+impl fmt::Display for LogicParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.file() {
+            Some(file) => write!(f, "{}:", file.display())?,
+            None => write!(f, "<input>:")?,
+        }
+
+        match self.position() {
+            Some((line, column)) => write!(f, "{}:{}: ", line, column)?,
+            None => write!(f, " ")?,
+        }
+
+        match self {
+            LogicParseError::Io { source, .. } => write!(f, "failed to read file: {}", source),
+            LogicParseError::Syntax { message, .. } => write!(f, "{}", message),
+            LogicParseError::UnknownType { name, .. } => {
+                write!(f, "unknown component type {:?}", name)
+            }
+            LogicParseError::DuplicateComponent { id, .. } => {
+                write!(f, "component {:?} is defined more than once", id)
+            }
+            LogicParseError::UnknownComponent { id, .. } => {
+                write!(f, "no component named {:?} is defined", id)
+            }
+            LogicParseError::UnknownPort {
+                component, port, ..
+            } => write!(f, "component {:?} has no port {:?}", component, port),
+        }
+    }
+}
+
+impl std::error::Error for LogicParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LogicParseError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Why the connections of a circuit could not be resolved to the gate inputs they drive.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum NetlistError {
+    /// Only gates and pins are supported: subcircuit instances have to be expanded with
+    /// `Project::flatten` before simulating, tabulating, minimizing or comparing a circuit
+    UnsupportedComponent(String),
+    UnknownComponent(String),
+    UnknownPort {
+        component: String,
+        port: String,
+    },
+    /// A connection into a component with several inputs that does not say which one
+    AmbiguousConnection {
+        from: String,
+        to: String,
+    },
+}
+
+impl fmt::Display for NetlistError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetlistError::UnsupportedComponent(id) => write!(
+                f,
+                "component {:?} is a subcircuit, flatten the circuit first",
+                id
+            ),
+            NetlistError::UnknownComponent(id) => write!(f, "no component named {:?}", id),
+            NetlistError::UnknownPort { component, port } => {
+                write!(f, "component {:?} has no port {:?}", component, port)
+            }
+            NetlistError::AmbiguousConnection { from, to } => write!(
+                f,
+                "the connection from {:?} to {:?} does not say which input it drives",
+                from, to
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NetlistError {}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum SimulationError {
+    Netlist(NetlistError),
+    /// The `delay` attribute of a component is not a whole number
+    InvalidDelay {
+        component: String,
+        value: String,
+    },
+    /// No input pin has this name or id
+    UnknownPin(String),
+    /// The pin cannot be set to this value, e.g. floating without `tristate`
+    InvalidValue {
+        pin: String,
+        value: Value,
+    },
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimulationError::Netlist(source) => write!(f, "{}", source),
+            SimulationError::InvalidDelay { component, value } => write!(
+                f,
+                "component {:?} has an invalid delay: {:?}",
+                component, value
+            ),
+            SimulationError::UnknownPin(name) => write!(f, "no input pin named {:?}", name),
+            SimulationError::InvalidValue { pin, value } => {
+                write!(f, "input pin {:?} cannot be set to {}", pin, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimulationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SimulationError::Netlist(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<NetlistError> for SimulationError {
+    fn from(source: NetlistError) -> SimulationError {
+        SimulationError::Netlist(source)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum TruthTableError {
+    /// The circuit has more input pins than the table was allowed to enumerate
+    TooManyInputs {
+        inputs: usize,
+        max: usize,
+    },
+    /// The outputs never settled for these input values, in input order
+    Oscillation(Vec<bool>),
+    Simulation(SimulationError),
+}
+
+impl fmt::Display for TruthTableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TruthTableError::TooManyInputs { inputs, max } => write!(
+                f,
+                "the circuit has {} inputs, more than the {} a truth table is made for",
+                inputs, max
+            ),
+            TruthTableError::Oscillation(inputs) => {
+                let inputs: String = inputs
+                    .iter()
+                    .map(|input| if *input { '1' } else { '0' })
+                    .collect();
+                write!(f, "the circuit oscillates for inputs {}", inputs)
+            }
+            TruthTableError::Simulation(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl std::error::Error for TruthTableError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TruthTableError::Simulation(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<SimulationError> for TruthTableError {
+    fn from(source: SimulationError) -> TruthTableError {
+        TruthTableError::Simulation(source)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum EquivalenceError {
+    /// The circuits do not have input pins with the same labels (or ids)
+    InputsDiffer {
+        reference: Vec<String>,
+        submission: Vec<String>,
+    },
+    /// The circuits do not have output pins with the same labels (or ids)
+    OutputsDiffer {
+        reference: Vec<String>,
+        submission: Vec<String>,
+    },
+    /// A feedback loop through this component, which BDDs cannot compare
+    Sequential(String),
+    /// A component with no driver on any input, or with several on one, whose output BDDs
+    /// cannot compare
+    Undefined(String),
+    Flatten(FlattenError),
+    Netlist(NetlistError),
+    TruthTable(TruthTableError),
+}
+
+impl fmt::Display for EquivalenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EquivalenceError::InputsDiffer {
+                reference,
+                submission,
+            } => write!(
+                f,
+                "the inputs {} do not match the reference inputs {}",
+                submission.join(", "),
+                reference.join(", ")
+            ),
+            EquivalenceError::OutputsDiffer {
+                reference,
+                submission,
+            } => write!(
+                f,
+                "the outputs {} do not match the reference outputs {}",
+                submission.join(", "),
+                reference.join(", ")
+            ),
+            EquivalenceError::Sequential(id) => write!(
+                f,
+                "component {:?} is on a feedback loop, which only exhaustive checking supports",
+                id
+            ),
+            EquivalenceError::Undefined(id) => write!(
+                f,
+                "component {:?} has no single driver, which only exhaustive checking supports",
+                id
+            ),
+            EquivalenceError::Flatten(source) => write!(f, "{}", source),
+            EquivalenceError::Netlist(source) => write!(f, "{}", source),
+            EquivalenceError::TruthTable(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl std::error::Error for EquivalenceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EquivalenceError::Flatten(source) => Some(source),
+            EquivalenceError::Netlist(source) => Some(source),
+            EquivalenceError::TruthTable(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<FlattenError> for EquivalenceError {
+    fn from(source: FlattenError) -> EquivalenceError {
+        EquivalenceError::Flatten(source)
+    }
+}
+
+impl From<NetlistError> for EquivalenceError {
+    fn from(source: NetlistError) -> EquivalenceError {
+        EquivalenceError::Netlist(source)
+    }
+}
+
+impl From<TruthTableError> for EquivalenceError {
+    fn from(source: TruthTableError) -> EquivalenceError {
+        EquivalenceError::TruthTable(source)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum MinimizeError {
+    /// A row of the function is not below `2^inputs`
+    RowOutOfRange {
+        row: usize,
+        inputs: usize,
+    },
+    /// The function has more inputs than a row number has bits
+    TooManyInputs {
+        inputs: usize,
+        max: usize,
+    },
+    /// The components on a feedback loop, whose outputs depend on more than the inputs
+    Sequential(Vec<String>),
+    Netlist(NetlistError),
+    TruthTable(TruthTableError),
+}
+
+impl fmt::Display for MinimizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MinimizeError::RowOutOfRange { row, inputs } => write!(
+                f,
+                "row {} is out of range for a function of {} inputs",
+                row, inputs
+            ),
+            MinimizeError::TooManyInputs { inputs, max } => write!(
+                f,
+                "the function has {} inputs, more than the {} it can be minimized with",
+                inputs, max
+            ),
+            MinimizeError::Sequential(cycle) => write!(
+                f,
+                "{} form a feedback loop, so the circuit has no truth table to minimize",
+                cycle.join(", ")
+            ),
+            MinimizeError::Netlist(source) => write!(f, "{}", source),
+            MinimizeError::TruthTable(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl std::error::Error for MinimizeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MinimizeError::Netlist(source) => Some(source),
+            MinimizeError::TruthTable(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<NetlistError> for MinimizeError {
+    fn from(source: NetlistError) -> MinimizeError {
+        MinimizeError::Netlist(source)
+    }
+}
+
+impl From<TruthTableError> for MinimizeError {
+    fn from(source: TruthTableError) -> MinimizeError {
+        MinimizeError::TruthTable(source)
+    }
+}
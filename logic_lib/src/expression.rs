@@ -0,0 +1,376 @@
+use std::fmt;
+
+use crate::appearance::port_name;
+use crate::circuit::Circuit;
+use crate::error::NetlistError;
+use crate::netlist::{
+    exactly_one, is_input_pin, is_negated, is_odd_parity, is_output_pin, Netlist,
+};
+use crate::types::{Component, ComponentType};
+
+/// A Boolean function of the input pins of a circuit.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Expression {
+    /// An input pin, or the output of a gate met again on a feedback loop
+    Variable(String),
+    /// A signal with no driver or with several, shown as `X`
+    Unknown,
+    /// A function that does not depend on its inputs, as found by minimization
+    Constant(bool),
+    Not(Box<Expression>),
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+    Xor(Vec<Expression>),
+}
+
+/// How `Expression::render` writes operators and names.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+pub enum Syntax {
+    /// `NOT`, `AND`, `OR` and `XOR`
+    #[default]
+    Plain,
+    /// `\overline`, `\land`, `\lor` and `\oplus`, for math mode
+    Latex,
+    /// `!`, `&`, `|` and `^`, which mean the same in Rust and C; names become identifiers
+    /// and constants `true` and `false`
+    C,
+}
+
+/// The expression computed for an output pin.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Equation {
+    /// Label (or id) of the output pin
+    pub output: String,
+    pub expression: Expression,
+    /// Feedback loops met while walking back from the output, each as the ids of the components
+    /// around it. The expression refers to the first one by the output pin it drives, or by its
+    /// id if it drives none.
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl Expression {
+    pub fn render(&self, syntax: Syntax) -> String {
+        match self {
+            Expression::Variable(name) => render_name(name, syntax),
+            Expression::Unknown => String::from("X"),
+            Expression::Constant(value) => match syntax {
+                Syntax::C => value.to_string(),
+                _ => String::from(if *value { "1" } else { "0" }),
+            },
+            Expression::Not(operand) => match syntax {
+                Syntax::Plain => format!("NOT {}", render_operand(operand, syntax)),
+                Syntax::Latex => format!("\\overline{{{}}}", operand.render(syntax)),
+                Syntax::C => format!("!{}", render_operand(operand, syntax)),
+            },
+            Expression::And(operands) => render_operands(operands, ["AND", "\\land", "&"], syntax),
+            Expression::Or(operands) => render_operands(operands, ["OR", "\\lor", "|"], syntax),
+            Expression::Xor(operands) => render_operands(operands, ["XOR", "\\oplus", "^"], syntax),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(Syntax::Plain))
+    }
+}
+
+impl Equation {
+    /// The equation as `output = expression`.
+    pub fn render(&self, syntax: Syntax) -> String {
+        format!(
+            "{} = {}",
+            render_name(&self.output, syntax),
+            self.expression.render(syntax)
+        )
+    }
+}
+
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(Syntax::Plain))
+    }
+}
+
+impl Circuit {
+    /// Walks back from every output pin to the input pins and returns the function it computes,
+    /// in the order of the output pins.
+    ///
+    /// Unconnected gate inputs are left out, like Logisim does by default. Feedback loops, like
+    /// the one of a latch, are cut where a gate is met again, so a NOR latch gives something
+    /// like `Q = NOT (R OR NOT (S OR Q))` and reports the loop in `Equation::cycles`.
+    pub fn expressions(&self) -> Result<Vec<Equation>, NetlistError> {
+        let netlist = Netlist::new(self)?;
+        let mut on_loop = vec![false; netlist.components.len()];
+        for index in netlist.loops().into_iter().flatten() {
+            on_loop[index] = true;
+        }
+
+        let mut equations: Vec<Equation> = Vec::new();
+        for (index, component) in netlist.components.iter().enumerate() {
+            if !is_output_pin(component) {
+                continue;
+            }
+
+            let mut walk = Walk {
+                netlist: &netlist,
+                on_loop: &on_loop,
+                path: Vec::new(),
+                on_path: vec![false; netlist.components.len()],
+                known: vec![None; netlist.components.len()],
+                cycles: Vec::new(),
+            };
+            let expression = walk.signal(index);
+
+            equations.push(Equation {
+                output: port_name(&netlist.components, component),
+                expression,
+                cycles: walk.cycles,
+            });
+        }
+
+        Ok(equations)
+    }
+}
+
+/// State of the walk back from one output pin.
+struct Walk<'a> {
+    netlist: &'a Netlist,
+    on_loop: &'a [bool],
+    /// The components between the output pin and the one being visited, each with the
+    /// operands worked out so far, one per input
+    path: Vec<(usize, Vec<Option<Expression>>)>,
+    on_path: Vec<bool>,
+    /// The expressions of input pins, and of components off feedback loops read by several
+    /// others, which come out the same whichever way they are reached
+    known: Vec<Option<Expression>>,
+    cycles: Vec<Vec<String>>,
+}
+
+impl Walk<'_> {
+    /// The expression of a component, walked without recursion so that long chains of gates
+    /// do not run out of stack.
+    fn signal(&mut self, index: usize) -> Expression {
+        if let Some(expression) = self.reached(index) {
+            return expression;
+        }
+        self.enter(index);
+
+        loop {
+            let (index, operands) = self.path.last_mut().unwrap();
+            let index = *index;
+            let drivers = &self.netlist.inputs[index];
+
+            if operands.len() < drivers.len() {
+                let input = operands.len();
+                match drivers[input].as_slice() {
+                    [] => operands.push(None),
+                    [driver] => {
+                        let driver = *driver;
+                        match self.reached(driver) {
+                            Some(expression) => self.push_operand(expression),
+                            None => self.enter(driver),
+                        }
+                    }
+                    _ => operands.push(Some(Expression::Unknown)),
+                }
+                continue;
+            }
+
+            let (index, operands) = self.path.pop().unwrap();
+            self.on_path[index] = false;
+            let expression = gate(&self.netlist.components[index], operands);
+            // Only worth keeping for components read more than once
+            if !self.on_loop[index] && self.netlist.fanout[index].len() > 1 {
+                self.known[index] = Some(expression.clone());
+            }
+
+            if self.path.is_empty() {
+                return expression;
+            }
+            self.push_operand(expression);
+        }
+    }
+
+    /// The expression of a component that needs no walking: an input pin, a gate met again on
+    /// a loop, or one already worked out.
+    fn reached(&mut self, index: usize) -> Option<Expression> {
+        if let Some(expression) = &self.known[index] {
+            return Some(expression.clone());
+        }
+
+        let component = &self.netlist.components[index];
+        if is_input_pin(component) {
+            let expression = Expression::Variable(port_name(&self.netlist.components, component));
+            self.known[index] = Some(expression.clone());
+            return Some(expression);
+        }
+
+        if self.on_path[index] {
+            let start = self
+                .path
+                .iter()
+                .position(|(visited, _)| *visited == index)
+                .unwrap();
+            let cycle: Vec<String> = self.path[start..]
+                .iter()
+                .map(|(visited, _)| self.netlist.components[*visited].id.clone())
+                .collect();
+            if !self.cycles.contains(&cycle) {
+                self.cycles.push(cycle);
+            }
+            return Some(Expression::Variable(self.loop_name(index)));
+        }
+
+        None
+    }
+
+    fn enter(&mut self, index: usize) {
+        self.path.push((index, Vec::new()));
+        self.on_path[index] = true;
+    }
+
+    /// Hands the expression of a driver to the component reading it, on its next input.
+    fn push_operand(&mut self, expression: Expression) {
+        let (index, operands) = self.path.last_mut().unwrap();
+        let component = &self.netlist.components[*index];
+        operands.push(Some(if is_negated(component, operands.len()) {
+            not(expression)
+        } else {
+            expression
+        }));
+    }
+
+    /// What a gate met again on a loop is called: the output pin it drives, if any.
+    fn loop_name(&self, index: usize) -> String {
+        self.netlist.fanout[index]
+            .iter()
+            .map(|reader| &self.netlist.components[*reader])
+            .find(|reader| is_output_pin(reader))
+            .map(|reader| port_name(&self.netlist.components, reader))
+            .unwrap_or_else(|| self.netlist.components[index].id.clone())
+    }
+}
+
+/// What a component computes from the expressions of its inputs, open ones left out.
+fn gate(component: &Component, inputs: Vec<Option<Expression>>) -> Expression {
+    let operands: Vec<Expression> = inputs.into_iter().flatten().collect();
+    if operands.is_empty() {
+        return Expression::Unknown;
+    }
+
+    match component.component_type {
+        ComponentType::PIN => operands.into_iter().next().unwrap(),
+        ComponentType::NOT => not(operands.into_iter().next().unwrap()),
+        ComponentType::AND => and(operands),
+        ComponentType::NAND => not(and(operands)),
+        ComponentType::OR => or(operands),
+        ComponentType::NOR => not(or(operands)),
+        ComponentType::XOR if is_odd_parity(component) => xor(operands),
+        ComponentType::XOR => one_high(operands),
+        ComponentType::XNOR if is_odd_parity(component) => not(xor(operands)),
+        ComponentType::XNOR => not(one_high(operands)),
+        ComponentType::Subcircuit(_) => unreachable!("subcircuits are rejected by `Netlist`"),
+    }
+}
+
+pub(crate) fn not(operand: Expression) -> Expression {
+    Expression::Not(Box::new(operand))
+}
+
+pub(crate) fn and(mut operands: Vec<Expression>) -> Expression {
+    if operands.len() == 1 {
+        operands.remove(0)
+    } else {
+        Expression::And(operands)
+    }
+}
+
+pub(crate) fn or(mut operands: Vec<Expression>) -> Expression {
+    if operands.len() == 1 {
+        operands.remove(0)
+    } else {
+        Expression::Or(operands)
+    }
+}
+
+fn xor(mut operands: Vec<Expression>) -> Expression {
+    if operands.len() == 1 {
+        operands.remove(0)
+    } else {
+        Expression::Xor(operands)
+    }
+}
+
+/// Logisim's default XOR, the same as parity for two inputs.
+fn one_high(operands: Vec<Expression>) -> Expression {
+    if operands.len() <= 2 {
+        return xor(operands);
+    }
+
+    or(exactly_one(operands.len())
+        .into_iter()
+        .map(|term| {
+            and(term
+                .into_iter()
+                .map(|(input, complemented)| {
+                    if complemented {
+                        not(operands[input].clone())
+                    } else {
+                        operands[input].clone()
+                    }
+                })
+                .collect())
+        })
+        .collect())
+}
+
+fn render_operand(operand: &Expression, syntax: Syntax) -> String {
+    match operand {
+        Expression::And(_) | Expression::Or(_) | Expression::Xor(_) => {
+            format!("({})", operand.render(syntax))
+        }
+        _ => operand.render(syntax),
+    }
+}
+
+/// Joins `operands` with the operator `operators` has for `syntax`, in `Syntax` order.
+fn render_operands(operands: &[Expression], operators: [&str; 3], syntax: Syntax) -> String {
+    let operator = match syntax {
+        Syntax::Plain => operators[0],
+        Syntax::Latex => operators[1],
+        Syntax::C => operators[2],
+    };
+
+    operands
+        .iter()
+        .map(|operand| render_operand(operand, syntax))
+        .collect::<Vec<String>>()
+        .join(format!(" {} ", operator).as_str())
+}
+
+fn render_name(name: &str, syntax: Syntax) -> String {
+    match syntax {
+        Syntax::Plain => String::from(name),
+        Syntax::Latex => {
+            let mut escaped = String::new();
+            for c in name.chars() {
+                if "_&%$#{}".contains(c) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            format!("\\mathrm{{{}}}", escaped)
+        }
+        Syntax::C => {
+            let identifier: String = name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            match identifier.chars().next() {
+                Some(first) if !first.is_ascii_digit() => identifier,
+                _ => format!("_{}", identifier),
+            }
+        }
+    }
+}
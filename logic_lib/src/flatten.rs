@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use crate::circuit::{Circuit, Net};
+use crate::error::FlattenError;
+use crate::project::Project;
+use crate::types::{is_identifier, Component, ComponentType};
+use crate::union_find::UnionFind;
+
+/// Components and nets collected while expanding a hierarchy; nets are merged through `sets`
+/// wherever a subcircuit port meets the pin it stands for.
+struct FlatNetlist {
+    components: Vec<Component>,
+    nets: Vec<Net>,
+    sets: UnionFind,
+}
+
+impl Project {
+    /// Expands every subcircuit instance of `name` recursively into a single gate-level circuit.
+    ///
+    /// Ids are prefixed by their hierarchical path, e.g. `cpu/alu/adder0/comp_3`, where each
+    /// instance is named by its label (or its id when the label is missing or not unique). The
+    /// pins of inner circuits disappear into the nets of their parent, so only the pins of
+    /// `name` itself remain. Flat circuits have no wires, as the coordinates of different
+    /// circuits do not share a canvas.
+    pub fn flatten(&self, name: &str) -> Result<Circuit, FlattenError> {
+        let mut netlist = FlatNetlist {
+            components: Vec::new(),
+            nets: Vec::new(),
+            sets: UnionFind::new(0),
+        };
+        let mut stack: Vec<String> = Vec::new();
+
+        self.expand(name, name, &mut stack, &mut netlist)?;
+
+        let mut merged: HashMap<usize, usize> = HashMap::new();
+        let mut nets: Vec<Net> = Vec::new();
+
+        for (index, net) in netlist.nets.into_iter().enumerate() {
+            let root = netlist.sets.find(index);
+
+            match merged.get(&root) {
+                Some(&merged_index) => {
+                    let merged_net: &mut Net = &mut nets[merged_index];
+                    merged_net.points.extend(net.points);
+                    merged_net.ports.extend(net.ports);
+                }
+                None => {
+                    merged.insert(root, nets.len());
+                    nets.push(Net {
+                        id: format!("net_{}", nets.len()),
+                        ..net
+                    });
+                }
+            }
+        }
+        nets.retain(|net| !net.ports.is_empty());
+
+        let mut circuit = Circuit::new(name);
+        for component in netlist.components {
+            circuit.add_component(component);
+        }
+        circuit.nets = nets;
+        circuit.derive_connections();
+
+        Ok(circuit)
+    }
+
+    /// Copies `name` into the netlist under `path`, returning the net each of its pins is on.
+    fn expand(
+        &self,
+        name: &str,
+        path: &str,
+        stack: &mut Vec<String>,
+        netlist: &mut FlatNetlist,
+    ) -> Result<HashMap<String, usize>, FlattenError> {
+        let circuit = self
+            .circuit(name)
+            .ok_or_else(|| FlattenError::UnknownCircuit(String::from(name)))?;
+
+        if stack.iter().any(|parent| parent == name) {
+            let mut cycle = stack.clone();
+            cycle.push(String::from(name));
+            return Err(FlattenError::RecursiveCircuit(cycle));
+        }
+        stack.push(String::from(name));
+
+        let top_level = stack.len() == 1;
+        // Pins of inner circuits are replaced by the nets they are connected to outside
+        let kept = |component: &Component| match component.component_type {
+            ComponentType::Subcircuit(_) => false,
+            ComponentType::PIN => top_level,
+            _ => true,
+        };
+
+        let first_net = netlist.nets.len();
+        let mut pin_nets: HashMap<String, usize> = HashMap::new();
+
+        for (index, net) in circuit.nets.iter().enumerate() {
+            let mut ports = Vec::new();
+
+            for port in &net.ports {
+                match circuit.component(&port.component) {
+                    Some(component) if kept(component) => {
+                        let mut port = port.clone();
+                        port.component = format!("{}/{}", path, port.component);
+                        ports.push(port);
+                    }
+                    Some(component) if component.component_type == ComponentType::PIN => {
+                        pin_nets.insert(component.id.clone(), first_net + index);
+                    }
+                    _ => (),
+                }
+            }
+
+            netlist.nets.push(Net {
+                id: net.id.clone(),
+                // Only the top-level circuit's coordinates mean anything in the flat netlist
+                points: if top_level {
+                    net.points.clone()
+                } else {
+                    Vec::new()
+                },
+                ports,
+            });
+            netlist.sets.push();
+        }
+
+        for component in &circuit.components {
+            match &component.component_type {
+                ComponentType::Subcircuit(subcircuit) => {
+                    let instance_path = format!("{}/{}", path, instance_name(circuit, component));
+                    let inner_pin_nets = self.expand(subcircuit, &instance_path, stack, netlist)?;
+
+                    for (index, net) in circuit.nets.iter().enumerate() {
+                        for port in &net.ports {
+                            if port.component != component.id {
+                                continue;
+                            }
+
+                            let inner_net =
+                                port.pin.as_ref().and_then(|pin| inner_pin_nets.get(pin));
+                            if let Some(&inner_net) = inner_net {
+                                netlist.sets.union(first_net + index, inner_net);
+                            }
+                        }
+                    }
+                }
+                _ if kept(component) => {
+                    let mut component = component.clone();
+                    component.id = format!("{}/{}", path, component.id);
+                    for port in &mut component.ports {
+                        port.component = component.id.clone();
+                    }
+                    netlist.components.push(component);
+                }
+                _ => (),
+            }
+        }
+
+        stack.pop();
+
+        Ok(pin_nets)
+    }
+}
+
+/// The label of a subcircuit instance when it identifies the instance and can be used in
+/// `.logic` code, otherwise its id.
+fn instance_name<'a>(circuit: &Circuit, instance: &'a Component) -> &'a str {
+    let label = instance
+        .attributes
+        .get("label")
+        .filter(|label| is_identifier(label));
+
+    match label {
+        Some(label) => {
+            let unique = circuit
+                .components
+                .iter()
+                .filter(|component| component.attributes.get("label") == Some(label))
+                .count()
+                == 1;
+
+            if unique {
+                label
+            } else {
+                &instance.id
+            }
+        }
+        None => &instance.id,
+    }
+}
@@ -1,2 +1,22 @@
+mod appearance;
+mod bdd;
 pub mod circ_parser;
+pub mod circuit;
+pub mod diagnostics;
+mod dot;
+pub mod equivalence;
+pub mod error;
+pub mod expression;
+mod flatten;
+pub mod logic_parser;
+pub mod minimize;
+mod netlist;
+pub mod project;
+pub mod simulator;
+mod svg;
+pub mod terminal;
+pub mod truth_table;
 pub mod types;
+mod union_find;
+mod verilog;
+mod vhdl;
@@ -0,0 +1,517 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    convert::Infallible,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::circuit::{Circuit, Connection};
+use crate::error::LogicParseError;
+use crate::types::{is_identifier, Component, ComponentType, Coordinate, Port, PortDirection};
+
+enum Source {
+    File(PathBuf),
+    Text(String),
+}
+
+/// Reads `.logic` code, as written by `Circuit::to_logic_code`, back into a `Circuit`.
+///
+/// `.logic` code has no geometry, so every component sits at (0,0) and the circuit has no wires
+/// or nets; how components are wired is only known from its connections.
+pub struct LogicParser {
+    source: Source,
+    file_path: Option<PathBuf>,
+}
+
+/// A `#define` block as written, before its type is checked.
+struct Define {
+    line: usize,
+    column: usize,
+    id: String,
+    component_type: Option<Value>,
+    circuit: Option<Value>,
+    attributes: BTreeMap<String, Value>,
+}
+
+/// The value of a `- key: value` line and where it starts.
+struct Value {
+    line: usize,
+    column: usize,
+    text: String,
+}
+
+/// An `#attach` line.
+struct Attach {
+    from: Endpoint,
+    to: Endpoint,
+}
+
+/// One end of an `#attach` line and where it starts.
+struct Endpoint {
+    line: usize,
+    column: usize,
+    component: String,
+    port: Option<String>,
+}
+
+impl LogicParser {
+    /// Creates a parser that reads the code from `file_path` when `parse` is called.
+    pub fn new(file_path: impl AsRef<Path>) -> LogicParser {
+        let file_path = file_path.as_ref().to_path_buf();
+
+        LogicParser {
+            source: Source::File(file_path.clone()),
+            file_path: Some(file_path),
+        }
+    }
+
+    /// Sets the file name reported in errors for in-memory sources.
+    pub fn with_file_name(mut self, file_name: impl Into<PathBuf>) -> LogicParser {
+        self.file_path = Some(file_name.into());
+        self
+    }
+
+    /// Parses the code into a circuit named after the file, or `main` for in-memory sources.
+    pub fn parse(&self) -> Result<Circuit, LogicParseError> {
+        let code = match &self.source {
+            Source::File(path) => {
+                std::fs::read_to_string(path).map_err(|source| LogicParseError::Io {
+                    file: self.file_path.clone(),
+                    source,
+                })?
+            }
+            Source::Text(text) => text.clone(),
+        };
+
+        let name = self
+            .file_path
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("main");
+
+        let (defines, attaches) = self.read_lines(&code)?;
+
+        let mut circuit = Circuit::new(name);
+        for define in defines {
+            circuit.add_component(self.build_component(define)?);
+        }
+
+        for Attach { from, to } in attaches {
+            self.check_endpoint(&circuit, &from)?;
+            self.check_endpoint(&circuit, &to)?;
+
+            circuit.add_connection(Connection {
+                from: from.component,
+                from_port: from.port,
+                to: to.component,
+                to_port: to.port,
+            });
+        }
+
+        Ok(circuit)
+    }
+
+    /// Splits the code into `#define` blocks and `#attach` lines.
+    fn read_lines(&self, code: &str) -> Result<(Vec<Define>, Vec<Attach>), LogicParseError> {
+        let mut defines: Vec<Define> = Vec::new();
+        let mut attaches: Vec<Attach> = Vec::new();
+        let mut ids: HashSet<String> = HashSet::new();
+        // Whether `- key: value` lines belong to the last `#define`
+        let mut in_define = false;
+
+        for (index, text) in code.lines().enumerate() {
+            let line = index + 1;
+            let words = words(text);
+
+            let Some(&(column, first)) = words.first() else {
+                continue;
+            };
+
+            match first {
+                "#define" => {
+                    let &[_, (column, id)] = words.as_slice() else {
+                        return Err(self.syntax(line, column, "expected `#define <id>`"));
+                    };
+                    self.check_id(line, column, id)?;
+
+                    if !ids.insert(String::from(id)) {
+                        return Err(LogicParseError::DuplicateComponent {
+                            file: self.file_path.clone(),
+                            line,
+                            column,
+                            id: String::from(id),
+                        });
+                    }
+
+                    defines.push(Define {
+                        line,
+                        column,
+                        id: String::from(id),
+                        component_type: None,
+                        circuit: None,
+                        attributes: BTreeMap::new(),
+                    });
+                    in_define = true;
+                }
+                "#attach" => {
+                    let &[_, from, to] = words.as_slice() else {
+                        return Err(self.syntax(line, column, "expected `#attach <from> <to>`"));
+                    };
+
+                    attaches.push(Attach {
+                        from: self.read_endpoint(line, from)?,
+                        to: self.read_endpoint(line, to)?,
+                    });
+                    in_define = false;
+                }
+                _ if first.starts_with('#') => {
+                    return Err(self.syntax(
+                        line,
+                        column,
+                        &format!("unknown directive `{}`", first),
+                    ));
+                }
+                _ if first.starts_with('-') => {
+                    let define = match defines.last_mut() {
+                        Some(define) if in_define => define,
+                        _ => {
+                            return Err(self.syntax(
+                                line,
+                                column,
+                                "attribute outside of a `#define` block",
+                            ))
+                        }
+                    };
+
+                    self.read_attribute(line, column, text, define)?;
+                }
+                _ => {
+                    return Err(self.syntax(
+                        line,
+                        column,
+                        "expected `#define`, `#attach` or `- <key>: <value>`",
+                    ))
+                }
+            }
+        }
+
+        Ok((defines, attaches))
+    }
+
+    /// Reads a `- key: value` line starting at `column` into `define`.
+    fn read_attribute(
+        &self,
+        line: usize,
+        column: usize,
+        text: &str,
+        define: &mut Define,
+    ) -> Result<(), LogicParseError> {
+        // Byte offsets into `text`, as `column` counts characters
+        let dash = byte_offset(text, column);
+        let rest = &text[dash + 1..];
+        let Some((key, value)) = rest.split_once(':') else {
+            return Err(self.syntax(line, column, "expected `- <key>: <value>`"));
+        };
+
+        let key_offset = dash + 1 + (key.len() - key.trim_start().len());
+        let value_offset = dash + 1 + key.len() + 1 + (value.len() - value.trim_start().len());
+
+        let key_column = column_of(text, key_offset);
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(self.syntax(line, key_column, "expected an attribute name"));
+        }
+
+        let value = Value {
+            line,
+            column: column_of(text, value_offset),
+            text: String::from(value.trim()),
+        };
+
+        let slot = match key {
+            "type" => &mut define.component_type,
+            "circuit" => &mut define.circuit,
+            _ => {
+                if define.attributes.contains_key(key) {
+                    return Err(self.syntax(
+                        line,
+                        key_column,
+                        &format!("attribute `{}` is set twice", key),
+                    ));
+                }
+
+                define.attributes.insert(String::from(key), value);
+                return Ok(());
+            }
+        };
+
+        if slot.is_some() {
+            return Err(self.syntax(
+                line,
+                key_column,
+                &format!("attribute `{}` is set twice", key),
+            ));
+        }
+        *slot = Some(value);
+
+        Ok(())
+    }
+
+    /// Reads `comp_0.out`, or just `comp_0`, found at `column`.
+    fn read_endpoint(
+        &self,
+        line: usize,
+        (column, word): (usize, &str),
+    ) -> Result<Endpoint, LogicParseError> {
+        let (component, port) = match word.split_once('.') {
+            Some((component, port)) => (component, Some(port)),
+            None => (word, None),
+        };
+
+        self.check_id(line, column, component)?;
+        if let Some(port) = port {
+            if !is_identifier(port) {
+                return Err(self.syntax(
+                    line,
+                    column + component.chars().count() + 1,
+                    &format!("invalid port name {:?}", port),
+                ));
+            }
+        }
+
+        Ok(Endpoint {
+            line,
+            column,
+            component: String::from(component),
+            port: port.map(String::from),
+        })
+    }
+
+    fn build_component(&self, define: Define) -> Result<Component, LogicParseError> {
+        let Some(type_value) = define.component_type else {
+            return Err(self.syntax(
+                define.line,
+                define.column,
+                &format!("component {:?} has no type", define.id),
+            ));
+        };
+
+        let (lib, name, component_type) = match type_value.text.as_str() {
+            "AND" => (Some(1), "AND Gate", ComponentType::AND),
+            "OR" => (Some(1), "OR Gate", ComponentType::OR),
+            "NAND" => (Some(1), "NAND Gate", ComponentType::NAND),
+            "NOR" => (Some(1), "NOR Gate", ComponentType::NOR),
+            "XOR" => (Some(1), "XOR Gate", ComponentType::XOR),
+            "XNOR" => (Some(1), "XNOR Gate", ComponentType::XNOR),
+            "NOT" => (Some(1), "NOT Gate", ComponentType::NOT),
+            "PIN" => (Some(0), "Pin", ComponentType::PIN),
+            "SUBCIRCUIT" => match &define.circuit {
+                Some(circuit) => (
+                    None,
+                    circuit.text.as_str(),
+                    ComponentType::Subcircuit(circuit.text.clone()),
+                ),
+                None => {
+                    return Err(self.syntax(
+                        type_value.line,
+                        type_value.column,
+                        "subcircuit instances need a `circuit` attribute",
+                    ))
+                }
+            },
+            other => {
+                return Err(LogicParseError::UnknownType {
+                    file: self.file_path.clone(),
+                    line: type_value.line,
+                    column: type_value.column,
+                    name: String::from(other),
+                })
+            }
+        };
+
+        if let (Some(circuit), false) = (
+            &define.circuit,
+            matches!(component_type, ComponentType::Subcircuit(_)),
+        ) {
+            return Err(self.syntax(
+                circuit.line,
+                circuit.column,
+                "only subcircuit instances have a `circuit` attribute",
+            ));
+        }
+
+        let mut component = Component {
+            lib,
+            name: String::from(name),
+            loc: Coordinate { x: 0, y: 0 },
+            id: define.id,
+            attributes: BTreeMap::new(),
+            component_type,
+            ports: Vec::new(),
+        };
+        component.ports = self.build_ports(&component, &define.attributes)?;
+        component.attributes = define
+            .attributes
+            .into_iter()
+            .map(|(key, value)| (key, value.text))
+            .collect();
+
+        Ok(component)
+    }
+
+    /// The ports `CircParser` would give the component, without their locations. Subcircuit
+    /// instances get none, as the circuit they refer to is not known here.
+    fn build_ports(
+        &self,
+        component: &Component,
+        attributes: &BTreeMap<String, Value>,
+    ) -> Result<Vec<Port>, LogicParseError> {
+        let port = |index: usize, direction: PortDirection, name: &str| Port {
+            component: component.id.clone(),
+            index,
+            direction,
+            name: String::from(name),
+            loc: component.loc,
+            pin: None,
+        };
+
+        let inputs = match component.component_type {
+            ComponentType::Subcircuit(_) => return Ok(Vec::new()),
+            ComponentType::PIN => {
+                let output = attributes
+                    .get("output")
+                    .is_some_and(|output| output.text == "true");
+
+                return Ok(vec![if output {
+                    port(0, PortDirection::Input, "in")
+                } else {
+                    port(0, PortDirection::Output, "out")
+                }]);
+            }
+            ComponentType::NOT => {
+                return Ok(vec![
+                    port(0, PortDirection::Output, "out"),
+                    port(1, PortDirection::Input, "in"),
+                ])
+            }
+            _ => match attributes.get("inputs") {
+                Some(inputs) => inputs
+                    .text
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|inputs| *inputs > 0)
+                    .ok_or_else(|| {
+                        self.syntax(
+                            inputs.line,
+                            inputs.column,
+                            &format!("invalid input count {:?}", inputs.text),
+                        )
+                    })?,
+                None => 2,
+            },
+        };
+
+        let mut ports = vec![port(0, PortDirection::Output, "out")];
+        for index in 1..=inputs {
+            ports.push(port(index, PortDirection::Input, &format!("in{}", index)));
+        }
+
+        Ok(ports)
+    }
+
+    fn check_endpoint(
+        &self,
+        circuit: &Circuit,
+        endpoint: &Endpoint,
+    ) -> Result<(), LogicParseError> {
+        let Some(component) = circuit.component(&endpoint.component) else {
+            return Err(LogicParseError::UnknownComponent {
+                file: self.file_path.clone(),
+                line: endpoint.line,
+                column: endpoint.column,
+                id: endpoint.component.clone(),
+            });
+        };
+
+        match &endpoint.port {
+            // Without the referenced circuit, any port of a subcircuit instance is accepted
+            Some(port)
+                if !component.ports.is_empty()
+                    && !component.ports.iter().any(|known| &known.name == port) =>
+            {
+                Err(LogicParseError::UnknownPort {
+                    file: self.file_path.clone(),
+                    line: endpoint.line,
+                    column: endpoint.column + endpoint.component.chars().count() + 1,
+                    component: endpoint.component.clone(),
+                    port: port.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Ids are made of identifiers separated by `/`, like the ones of flattened circuits.
+    fn check_id(&self, line: usize, column: usize, id: &str) -> Result<(), LogicParseError> {
+        if id.split('/').all(is_identifier) {
+            Ok(())
+        } else {
+            Err(self.syntax(line, column, &format!("invalid component id {:?}", id)))
+        }
+    }
+
+    fn syntax(&self, line: usize, column: usize, message: &str) -> LogicParseError {
+        LogicParseError::Syntax {
+            file: self.file_path.clone(),
+            line,
+            column,
+            message: String::from(message),
+        }
+    }
+}
+
+impl FromStr for LogicParser {
+    type Err = Infallible;
+
+    /// Creates a parser over `.logic` code held in memory.
+    fn from_str(code: &str) -> Result<LogicParser, Infallible> {
+        Ok(LogicParser {
+            source: Source::Text(String::from(code)),
+            file_path: None,
+        })
+    }
+}
+
+/// The whitespace-separated words of a line, with the 1-based column each starts at.
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words: Vec<(usize, &str)> = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (offset, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(word_start)) => {
+                words.push((column_of(text, word_start), &text[word_start..offset]));
+                start = None;
+            }
+            (false, None) => start = Some(offset),
+            _ => (),
+        }
+    }
+    if let Some(word_start) = start {
+        words.push((column_of(text, word_start), &text[word_start..]));
+    }
+
+    words
+}
+
+fn column_of(text: &str, offset: usize) -> usize {
+    text[..offset].chars().count() + 1
+}
+
+fn byte_offset(text: &str, column: usize) -> usize {
+    text.char_indices()
+        .nth(column - 1)
+        .map(|(offset, _)| offset)
+        .unwrap_or(text.len())
+}
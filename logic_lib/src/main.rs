@@ -1,12 +1,49 @@
 use std::path::Path;
 
 use logic_lib::circ_parser::CircParser;
+use logic_lib::circuit::Circuit;
+use logic_lib::terminal::Charset;
 
 fn main() {
-    let mut circ_parser = CircParser::new(Path::new("./tests/test.circ"));
-    circ_parser.parse();
-    let generated_code = circ_parser.transpile_to_logic_code();
+    // `draw [file.circ] [--ascii]` prints the main circuit instead of converting it
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|command| command == "draw") {
+        draw(&args[1..]);
+        return;
+    }
+
+    let circuit = parse(Path::new("./tests/test.circ"));
 
     // Save the generated code to a file
-    std::fs::write("./tests/test.logic", generated_code).expect("Unable to write file");
+    std::fs::write("./tests/test.logic", circuit.to_logic_code()).expect("Unable to write file");
+}
+
+fn draw(args: &[String]) {
+    let charset = if args.iter().any(|arg| arg == "--ascii") {
+        Charset::Ascii
+    } else {
+        Charset::Unicode
+    };
+    let file = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map_or("./tests/test.circ", |file| file.as_str());
+
+    print!("{}", parse(Path::new(file)).to_terminal(charset));
+}
+
+/// The main circuit of `file`, with its warnings printed, or exits on errors.
+fn parse(file: &Path) -> Circuit {
+    let parsed = CircParser::new(file)
+        .parse_with_diagnostics()
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        });
+
+    for diagnostic in &parsed.diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+
+    parsed.value
 }
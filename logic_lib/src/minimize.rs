@@ -0,0 +1,534 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashSet},
+    fmt,
+};
+
+use crate::circuit::Circuit;
+use crate::error::MinimizeError;
+use crate::expression::{and, not, or, Expression};
+use crate::netlist::Netlist;
+use crate::simulator::Value;
+use crate::truth_table::TruthTable;
+use crate::types::ComponentType;
+
+/// A Boolean function given by the rows of its truth table where it is high, numbered like
+/// `TruthTable` rows with the first input as the most significant bit.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Function {
+    pub inputs: Vec<String>,
+    pub ones: Vec<usize>,
+    /// Rows where the value does not matter; rows also listed in `ones` are high
+    pub dont_cares: Vec<usize>,
+}
+
+/// The smallest sum of products and product of sums of a function, with the gates each takes.
+///
+/// Gates are counted as drawn with AND, OR and NOT only: one multi-input AND or OR per term
+/// with more than one literal, one gate joining the terms, and one NOT per input used
+/// complemented. Circuits can do better with XOR, XNOR, NAND and NOR gates or with more than
+/// two levels, so parity functions in particular cost more here than they do drawn.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Minimized {
+    pub sum_of_products: Expression,
+    pub sum_of_products_gates: usize,
+    pub product_of_sums: Expression,
+    pub product_of_sums_gates: usize,
+    /// Whether both forms are known to be minimal; large irregular functions can take too long
+    /// to prove, and then get the best forms found within a fixed number of steps
+    pub exact: bool,
+}
+
+/// How far the gates of a circuit are from the minimal forms of its outputs.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MinimizationReport {
+    pub circuit: String,
+    /// Gates the circuit is drawn with
+    pub gates: usize,
+    /// Names of the output pins with their minimal forms
+    pub outputs: Vec<(String, Minimized)>,
+}
+
+/// A product term: `value` gives the inputs it needs high or low, except the ones in
+/// `dont_care`, whose bits in `value` are zero.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+struct Cube {
+    value: usize,
+    dont_care: usize,
+}
+
+impl Function {
+    /// A function of `inputs`, refusing rows that are not below `2^inputs`.
+    pub fn new(
+        inputs: Vec<String>,
+        ones: Vec<usize>,
+        dont_cares: Vec<usize>,
+    ) -> Result<Function, MinimizeError> {
+        let function = Function {
+            inputs,
+            ones,
+            dont_cares,
+        };
+        function.rows()?;
+
+        Ok(function)
+    }
+
+    /// Minimizes the function with the Quine–McCluskey method, picking the cover with the
+    /// fewest terms and then the fewest literals. Don't-care rows are used wherever they make
+    /// a term larger.
+    pub fn minimize(&self) -> Result<Minimized, MinimizeError> {
+        let rows = self.rows()?;
+        let ones: BTreeSet<usize> = self.ones.iter().copied().collect();
+        let dont_cares: BTreeSet<usize> = self
+            .dont_cares
+            .iter()
+            .copied()
+            .filter(|row| !ones.contains(row))
+            .collect();
+        let zeros: BTreeSet<usize> = (0..rows)
+            .filter(|row| !ones.contains(row) && !dont_cares.contains(row))
+            .collect();
+
+        let (high, high_exact) = self.cover(&ones, &dont_cares);
+        let (low, low_exact) = self.cover(&zeros, &dont_cares);
+
+        Ok(Minimized {
+            sum_of_products: self.sum_of_products(&high),
+            sum_of_products_gates: self.gates(&high, false),
+            product_of_sums: self.product_of_sums(&low),
+            product_of_sums_gates: self.gates(&low, true),
+            exact: high_exact && low_exact,
+        })
+    }
+
+    /// The number of rows of the truth table, checking that every row given is one of them.
+    fn rows(&self) -> Result<usize, MinimizeError> {
+        let inputs = self.inputs.len();
+        let rows = u32::try_from(inputs)
+            .ok()
+            .and_then(|inputs| 1_usize.checked_shl(inputs))
+            .ok_or(MinimizeError::TooManyInputs {
+                inputs,
+                max: usize::BITS as usize - 1,
+            })?;
+
+        match self
+            .ones
+            .iter()
+            .chain(&self.dont_cares)
+            .find(|row| **row >= rows)
+        {
+            Some(row) => Err(MinimizeError::RowOutOfRange { row: *row, inputs }),
+            None => Ok(rows),
+        }
+    }
+
+    /// The smallest set of cubes covering `rows` and nothing outside `rows` and `dont_cares`,
+    /// and whether it is known to be the smallest.
+    fn cover(&self, rows: &BTreeSet<usize>, dont_cares: &BTreeSet<usize>) -> (Vec<Cube>, bool) {
+        if rows.is_empty() {
+            return (Vec::new(), true);
+        }
+
+        let mut primes = prime_implicants(
+            self.inputs.len(),
+            rows.iter().chain(dont_cares).copied().collect(),
+        );
+        // Larger cubes first, so the first cover found is already a good bound
+        primes.sort_by_key(|prime| Reverse(prime.dont_care.count_ones()));
+
+        let rows: Vec<usize> = rows.iter().copied().collect();
+        let mut search = CoverSearch {
+            inputs: self.inputs.len(),
+            primes: &primes,
+            covering: rows
+                .iter()
+                .map(|row| {
+                    (0..primes.len())
+                        .filter(|prime| primes[*prime].covers(*row))
+                        .collect()
+                })
+                .collect(),
+            best: None,
+            steps: 0,
+        };
+        let rows: Vec<usize> = (0..rows.len()).collect();
+        search.greedy(&rows);
+        search.search(&rows, &mut Vec::new());
+
+        let mut cover: Vec<Cube> = search
+            .best
+            .map(|(primes, _)| primes)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|prime| primes[prime])
+            .collect();
+        cover.sort();
+        (cover, search.steps < SEARCH_STEPS)
+    }
+
+    /// The literals of a cube in input order, complemented where the input has to be low.
+    fn literals(&self, cube: &Cube) -> Vec<Expression> {
+        (0..self.inputs.len())
+            .filter(|input| cube.dont_care & self.bit(*input) == 0)
+            .map(|input| {
+                let variable = Expression::Variable(self.inputs[input].clone());
+                if cube.value & self.bit(input) == 0 {
+                    not(variable)
+                } else {
+                    variable
+                }
+            })
+            .collect()
+    }
+
+    fn sum_of_products(&self, cover: &[Cube]) -> Expression {
+        if cover.is_empty() {
+            return Expression::Constant(false);
+        }
+
+        or(cover
+            .iter()
+            .rev()
+            .map(|cube| match self.literals(cube) {
+                literals if literals.is_empty() => Expression::Constant(true),
+                literals => and(literals),
+            })
+            .collect())
+    }
+
+    /// The product of sums of a function from a cover of the rows where it is low.
+    fn product_of_sums(&self, cover: &[Cube]) -> Expression {
+        if cover.is_empty() {
+            return Expression::Constant(true);
+        }
+
+        and(cover
+            .iter()
+            .map(|cube| {
+                let literals: Vec<Expression> = self
+                    .literals(cube)
+                    .into_iter()
+                    .map(|literal| match literal {
+                        Expression::Not(variable) => *variable,
+                        variable => not(variable),
+                    })
+                    .collect();
+                if literals.is_empty() {
+                    Expression::Constant(false)
+                } else {
+                    or(literals)
+                }
+            })
+            .collect())
+    }
+
+    /// Gates for a two-level form of `cover`, whose literals are complemented where the input
+    /// bit equals `complemented`.
+    fn gates(&self, cover: &[Cube], complemented: bool) -> usize {
+        if cover.is_empty() || cover.iter().any(|cube| self.literal_count(cube) == 0) {
+            return 0;
+        }
+
+        let inverted = (0..self.inputs.len())
+            .filter(|input| {
+                cover.iter().any(|cube| {
+                    cube.dont_care & self.bit(*input) == 0
+                        && (cube.value & self.bit(*input) != 0) == complemented
+                })
+            })
+            .count();
+        let terms = cover
+            .iter()
+            .filter(|cube| self.literal_count(cube) > 1)
+            .count();
+        let joining = usize::from(cover.len() > 1);
+
+        inverted + terms + joining
+    }
+
+    fn literal_count(&self, cube: &Cube) -> usize {
+        self.inputs.len() - cube.dont_care.count_ones() as usize
+    }
+
+    fn bit(&self, input: usize) -> usize {
+        1 << (self.inputs.len() - 1 - input)
+    }
+}
+
+impl Cube {
+    fn covers(&self, row: usize) -> bool {
+        row & !self.dont_care == self.value
+    }
+}
+
+/// Merges cubes differing in a single input until no more can be merged.
+fn prime_implicants(inputs: usize, rows: Vec<usize>) -> Vec<Cube> {
+    let mut primes: Vec<Cube> = Vec::new();
+    let mut cubes: HashSet<Cube> = rows
+        .into_iter()
+        .map(|value| Cube {
+            value,
+            dont_care: 0,
+        })
+        .collect();
+
+    while !cubes.is_empty() {
+        let mut merged: HashSet<Cube> = HashSet::new();
+        let mut larger: HashSet<Cube> = HashSet::new();
+
+        for cube in &cubes {
+            for bit in (0..inputs).map(|input| 1 << input) {
+                if cube.dont_care & bit != 0 {
+                    continue;
+                }
+
+                let neighbour = Cube {
+                    value: cube.value ^ bit,
+                    dont_care: cube.dont_care,
+                };
+                if cubes.contains(&neighbour) {
+                    merged.insert(*cube);
+                    larger.insert(Cube {
+                        value: cube.value & !bit,
+                        dont_care: cube.dont_care | bit,
+                    });
+                }
+            }
+        }
+
+        primes.extend(cubes.difference(&merged));
+        cubes = larger;
+    }
+
+    primes.sort();
+    primes
+}
+
+/// Steps `CoverSearch` takes before settling for the best cover found so far.
+const SEARCH_STEPS: usize = 100_000;
+
+/// Branch and bound over the primes covering the row with the fewest choices left, starting
+/// from a greedy cover.
+struct CoverSearch<'a> {
+    inputs: usize,
+    primes: &'a [Cube],
+    /// For every row to cover, the primes covering it
+    covering: Vec<Vec<usize>>,
+    /// The cheapest cover so far with its terms and literals
+    best: Option<(Vec<usize>, (usize, usize))>,
+    steps: usize,
+}
+
+impl CoverSearch<'_> {
+    /// Covers the first rows with the primes that cover the most rows still uncovered.
+    fn greedy(&mut self, rows: &[usize]) {
+        let mut uncovered = rows.to_vec();
+        let mut chosen: Vec<usize> = Vec::new();
+
+        while !uncovered.is_empty() {
+            let covered = |prime: usize| {
+                uncovered
+                    .iter()
+                    .filter(|row| self.covering[**row].contains(&prime))
+                    .count()
+            };
+            let Some(prime) = (0..self.primes.len())
+                .max_by_key(|prime| (covered(*prime), Reverse(self.literals(*prime))))
+            else {
+                return;
+            };
+
+            uncovered.retain(|row| !self.covering[*row].contains(&prime));
+            chosen.push(prime);
+        }
+
+        let cost = self.cost(&chosen);
+        self.best = Some((chosen, cost));
+    }
+
+    fn search(&mut self, uncovered: &[usize], chosen: &mut Vec<usize>) {
+        if self.steps == SEARCH_STEPS {
+            return;
+        }
+        self.steps += 1;
+
+        let (terms, literals) = self.cost(chosen);
+        let needed = terms + self.independent_rows(uncovered);
+        if let Some((_, best)) = &self.best {
+            if (needed, literals) >= *best {
+                return;
+            }
+        }
+
+        let Some(row) = uncovered
+            .iter()
+            .copied()
+            .min_by_key(|row| self.covering[*row].len())
+        else {
+            self.best = Some((chosen.clone(), (terms, literals)));
+            return;
+        };
+
+        for prime in self.covering[row].clone() {
+            let remaining: Vec<usize> = uncovered
+                .iter()
+                .copied()
+                .filter(|other| !self.covering[*other].contains(&prime))
+                .collect();
+
+            chosen.push(prime);
+            self.search(&remaining, chosen);
+            chosen.pop();
+        }
+    }
+
+    /// A lower bound on the terms still needed: rows no single prime covers two of.
+    fn independent_rows(&self, uncovered: &[usize]) -> usize {
+        let mut rows = uncovered.to_vec();
+        rows.sort_by_key(|row| self.covering[*row].len());
+
+        let mut used = vec![false; self.primes.len()];
+        let mut independent = 0;
+        for row in rows {
+            if self.covering[row].iter().all(|prime| !used[*prime]) {
+                for prime in &self.covering[row] {
+                    used[*prime] = true;
+                }
+                independent += 1;
+            }
+        }
+
+        independent
+    }
+
+    fn cost(&self, primes: &[usize]) -> (usize, usize) {
+        (
+            primes.len(),
+            primes.iter().map(|prime| self.literals(*prime)).sum(),
+        )
+    }
+
+    fn literals(&self, prime: usize) -> usize {
+        self.inputs - self.primes[prime].dont_care.count_ones() as usize
+    }
+}
+
+impl TruthTable {
+    /// The function computed by an output pin, with the rows where it is `X` or `Z` as
+    /// don't-cares.
+    pub fn function(&self, output: &str) -> Option<Function> {
+        let column = self.outputs.iter().position(|name| name == output)?;
+
+        let mut ones: Vec<usize> = Vec::new();
+        let mut dont_cares: Vec<usize> = Vec::new();
+        for (row, values) in self.rows.iter().enumerate() {
+            match values.outputs[column] {
+                Value::One => ones.push(row),
+                Value::Zero => {}
+                Value::Unknown | Value::Floating => dont_cares.push(row),
+            }
+        }
+
+        Some(Function {
+            inputs: self.inputs.clone(),
+            ones,
+            dont_cares,
+        })
+    }
+}
+
+impl MinimizationReport {
+    /// Gates the minimal forms take together, the cheaper form for every output.
+    pub fn minimal_gates(&self) -> usize {
+        self.outputs
+            .iter()
+            .map(|(_, minimized)| minimized.gates())
+            .sum()
+    }
+
+    /// Gates that could be saved by drawing the minimal forms instead. A circuit smaller than
+    /// them, e.g. one using XOR gates, has nothing to save that way, so this is zero then
+    /// rather than negative.
+    pub fn gap(&self) -> usize {
+        self.gates.saturating_sub(self.minimal_gates())
+    }
+}
+
+impl Minimized {
+    /// Gates of the cheaper form.
+    pub fn gates(&self) -> usize {
+        self.sum_of_products_gates.min(self.product_of_sums_gates)
+    }
+}
+
+impl fmt::Display for MinimizationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (output, minimized) in &self.outputs {
+            writeln!(
+                f,
+                "{} = {} ({})",
+                output,
+                minimized.sum_of_products,
+                gate_count(minimized.sum_of_products_gates)
+            )?;
+            writeln!(
+                f,
+                "{} = {} ({})",
+                output,
+                minimized.product_of_sums,
+                gate_count(minimized.product_of_sums_gates)
+            )?;
+        }
+        write!(
+            f,
+            "{} has {}, the minimal forms need {}",
+            self.circuit,
+            gate_count(self.gates),
+            self.minimal_gates()
+        )
+    }
+}
+
+fn gate_count(gates: usize) -> String {
+    if gates == 1 {
+        String::from("1 gate")
+    } else {
+        format!("{} gates", gates)
+    }
+}
+
+impl Circuit {
+    /// Tabulates the circuit and minimizes every output. Circuits with feedback loops, like
+    /// latches, are refused: what they output depends on their state as well as their inputs.
+    pub fn minimize(&self) -> Result<MinimizationReport, MinimizeError> {
+        let netlist = Netlist::new(self)?;
+        if let Some(cycle) = netlist.loops().into_iter().next() {
+            return Err(MinimizeError::Sequential(
+                cycle
+                    .into_iter()
+                    .map(|index| netlist.components[index].id.clone())
+                    .collect(),
+            ));
+        }
+
+        let table = TruthTable::new(self)?;
+        let mut outputs: Vec<(String, Minimized)> = Vec::new();
+        for output in &table.outputs {
+            if let Some(function) = table.function(output) {
+                outputs.push((output.clone(), function.minimize()?));
+            }
+        }
+
+        Ok(MinimizationReport {
+            circuit: self.name.clone(),
+            gates: self
+                .components
+                .iter()
+                .filter(|component| component.component_type != ComponentType::PIN)
+                .count(),
+            outputs,
+        })
+    }
+}
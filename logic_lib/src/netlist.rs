@@ -0,0 +1,523 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::appearance::port_name;
+use crate::circuit::Circuit;
+use crate::error::NetlistError;
+use crate::types::{Component, ComponentType, Port};
+use crate::union_find::UnionFind;
+
+/// The gates and pins of a flat circuit with its connections resolved to the inputs they drive.
+pub(crate) struct Netlist {
+    pub components: Vec<Component>,
+    /// For every component and every one of its inputs, the components driving it
+    pub inputs: Vec<Vec<Vec<usize>>>,
+    /// For every component, the components reading its value
+    pub fanout: Vec<Vec<usize>>,
+}
+
+impl Netlist {
+    /// Resolves the connections of `circuit`, which must not contain subcircuit instances.
+    pub fn new(circuit: &Circuit) -> Result<Netlist, NetlistError> {
+        let components = circuit.components.clone();
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        for (position, component) in components.iter().enumerate() {
+            positions.entry(component.id.as_str()).or_insert(position);
+        }
+        let index_of = |id: &str| {
+            positions
+                .get(id)
+                .copied()
+                .ok_or_else(|| NetlistError::UnknownComponent(String::from(id)))
+        };
+
+        let mut inputs: Vec<Vec<Vec<usize>>> = Vec::new();
+        for component in &components {
+            if let ComponentType::Subcircuit(_) = component.component_type {
+                return Err(NetlistError::UnsupportedComponent(component.id.clone()));
+            }
+
+            inputs.push(vec![Vec::new(); input_ports(component).count()]);
+        }
+
+        let mut fanout: Vec<Vec<usize>> = vec![Vec::new(); components.len()];
+        // What is already in `inputs` and `fanout`, either of which can grow as long as the
+        // circuit, as driver and reader, with the input for `inputs`
+        let mut driven: HashSet<(usize, usize, usize)> = HashSet::new();
+        let mut linked: HashSet<(usize, usize)> = HashSet::new();
+        for connection in &circuit.connections {
+            let from = index_of(&connection.from)?;
+            let to = index_of(&connection.to)?;
+
+            if let Some(port) = &connection.from_port {
+                if !components[from]
+                    .ports
+                    .iter()
+                    .any(|known| &known.name == port && known.drives())
+                {
+                    return Err(NetlistError::UnknownPort {
+                        component: connection.from.clone(),
+                        port: port.clone(),
+                    });
+                }
+            }
+
+            let input = match &connection.to_port {
+                Some(port) => input_ports(&components[to])
+                    .position(|known| &known.name == port)
+                    .ok_or_else(|| NetlistError::UnknownPort {
+                        component: connection.to.clone(),
+                        port: port.clone(),
+                    })?,
+                // Old-style connections only say which component they go to, which is enough
+                // when it has a single input
+                None if inputs[to].len() == 1 => 0,
+                None => {
+                    return Err(NetlistError::AmbiguousConnection {
+                        from: connection.from.clone(),
+                        to: connection.to.clone(),
+                    })
+                }
+            };
+
+            if driven.insert((from, to, input)) {
+                inputs[to][input].push(from);
+            }
+            if linked.insert((from, to)) {
+                fanout[from].push(to);
+            }
+        }
+
+        Ok(Netlist {
+            components,
+            inputs,
+            fanout,
+        })
+    }
+
+    /// The feedback loops of the circuit, as the strongly connected components of its driver
+    /// graph that have more than one component or one driving itself. Each lists its components
+    /// in circuit order, and the loops are ordered by their first component.
+    pub fn loops(&self) -> Vec<Vec<usize>> {
+        let count = self.components.len();
+
+        // Components in the order a depth-first search along `fanout` is done with them
+        let mut finished: Vec<usize> = Vec::with_capacity(count);
+        let mut visited = vec![false; count];
+        for start in 0..count {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+
+            // Each component with the position of the next reader to visit
+            let mut stack: Vec<(usize, usize)> = vec![(start, 0)];
+            while let Some(&(index, next)) = stack.last() {
+                match self.fanout[index].get(next) {
+                    Some(&reader) => {
+                        stack.last_mut().unwrap().1 += 1;
+                        if !visited[reader] {
+                            visited[reader] = true;
+                            stack.push((reader, 0));
+                        }
+                    }
+                    None => {
+                        finished.push(index);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        // Going back along the drivers, from the last component finished, reaches exactly one
+        // strongly connected component at a time
+        let mut group: Vec<Option<usize>> = vec![None; count];
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for &start in finished.iter().rev() {
+            if group[start].is_some() {
+                continue;
+            }
+            group[start] = Some(groups.len());
+
+            let mut members: Vec<usize> = Vec::new();
+            let mut stack: Vec<usize> = vec![start];
+            while let Some(index) = stack.pop() {
+                members.push(index);
+                for &driver in self.inputs[index].iter().flatten() {
+                    if group[driver].is_none() {
+                        group[driver] = Some(groups.len());
+                        stack.push(driver);
+                    }
+                }
+            }
+            members.sort_unstable();
+            groups.push(members);
+        }
+
+        let mut loops: Vec<Vec<usize>> = groups
+            .into_iter()
+            .filter(|members| members.len() > 1 || self.fanout[members[0]].contains(&members[0]))
+            .collect();
+        loops.sort_unstable();
+        loops
+    }
+}
+
+/// The nets of a circuit rebuilt from its connections, so that circuits read from `.logic`
+/// code, which have no wires, get them too.
+pub(crate) struct Nets {
+    /// Every port seen, as a component index and port name
+    pub endpoints: Vec<(usize, String)>,
+    /// For every component, its endpoints
+    pub owned: Vec<Vec<usize>>,
+    pub lookup: HashMap<(usize, String), usize>,
+    /// For every endpoint, the net it is on
+    pub nets: Vec<usize>,
+    /// For every net, its size in endpoints
+    pub sizes: Vec<usize>,
+    pub names: Vec<String>,
+    /// For every net, whether it is named after a pin and so is a port of the module
+    pub ports: Vec<bool>,
+    /// Connections whose ports could not be worked out
+    pub unresolved: Vec<String>,
+}
+
+impl Nets {
+    pub fn new(circuit: &Circuit) -> Nets {
+        let components = &circuit.components;
+        let mut endpoints: Vec<(usize, String)> = Vec::new();
+        let mut lookup: HashMap<(usize, String), usize> = HashMap::new();
+        let mut sets = UnionFind::new(0);
+        let mut unresolved: Vec<String> = Vec::new();
+
+        let mut endpoint = |component: usize, port: &str| {
+            let key = (component, String::from(port));
+            *lookup.entry(key.clone()).or_insert_with(|| {
+                endpoints.push(key);
+                sets.push()
+            })
+        };
+
+        for (index, component) in components.iter().enumerate() {
+            for port in &component.ports {
+                endpoint(index, &port.name);
+            }
+        }
+
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        for (index, component) in components.iter().enumerate() {
+            positions.entry(component.id.as_str()).or_insert(index);
+        }
+
+        let mut unions: Vec<(usize, usize)> = Vec::new();
+        for connection in &circuit.connections {
+            let from = positions.get(connection.from.as_str()).copied();
+            let to = positions.get(connection.to.as_str()).copied();
+            let (Some(from), Some(to)) = (from, to) else {
+                unresolved.push(format!(
+                    "{} -> {}",
+                    connection.source(),
+                    connection.target()
+                ));
+                continue;
+            };
+
+            let from_port = connection.from_port.clone().or_else(|| {
+                components[from]
+                    .ports
+                    .iter()
+                    .find(|port| port.drives())
+                    .map(|port| port.name.clone())
+            });
+            let to_port = connection.to_port.clone().or_else(|| {
+                let mut inputs = components[to].ports.iter().filter(|port| port.reads());
+                match (inputs.next(), inputs.next()) {
+                    (Some(port), None) => Some(port.name.clone()),
+                    _ => None,
+                }
+            });
+            let (Some(from_port), Some(to_port)) = (from_port, to_port) else {
+                unresolved.push(format!(
+                    "{} -> {}",
+                    connection.source(),
+                    connection.target()
+                ));
+                continue;
+            };
+
+            unions.push((endpoint(from, &from_port), endpoint(to, &to_port)));
+        }
+
+        for (a, b) in unions {
+            sets.union(a, b);
+        }
+
+        // Nets are numbered in the order their first endpoint was seen
+        let mut roots: HashMap<usize, usize> = HashMap::new();
+        let mut nets: Vec<usize> = Vec::new();
+        let mut sizes: Vec<usize> = Vec::new();
+        for element in 0..endpoints.len() {
+            let root = sets.find(element);
+            let next = roots.len();
+            let net = *roots.entry(root).or_insert(next);
+            if net == sizes.len() {
+                sizes.push(0);
+            }
+            sizes[net] += 1;
+            nets.push(net);
+        }
+
+        let mut owned: Vec<Vec<usize>> = vec![Vec::new(); components.len()];
+        let mut input_pins: Vec<usize> = vec![0; sizes.len()];
+        for (element, (component, _)) in endpoints.iter().enumerate() {
+            owned[*component].push(element);
+            if is_input_pin(&components[*component]) {
+                input_pins[nets[element]] += 1;
+            }
+        }
+
+        // Nets are named after an input pin on them, else an output pin, else numbered. Input
+        // pins sharing a net all drive it, so none of them can stand for it.
+        let mut names: Vec<Option<String>> = vec![None; sizes.len()];
+        for pins in [is_input_pin, is_output_pin] {
+            for (element, (component, _)) in endpoints.iter().enumerate() {
+                let (pin, net) = (&components[*component], nets[element]);
+                let shared = is_input_pin(pin) && input_pins[net] > 1;
+                if pins(pin) && names[net].is_none() && !shared {
+                    names[net] = Some(port_name(components, pin));
+                }
+            }
+        }
+        let ports: Vec<bool> = names.iter().map(Option::is_some).collect();
+        let taken: HashSet<String> = names.iter().flatten().cloned().collect();
+        let mut numbered = 0;
+        let names = names
+            .into_iter()
+            .map(|name| {
+                name.unwrap_or_else(|| loop {
+                    let name = format!("net_{}", numbered);
+                    numbered += 1;
+                    if !taken.contains(&name) {
+                        break name;
+                    }
+                })
+            })
+            .collect();
+
+        Nets {
+            endpoints,
+            owned,
+            lookup,
+            nets,
+            sizes,
+            names,
+            ports,
+            unresolved,
+        }
+    }
+
+    pub fn net(&self, component: usize, port: &str) -> Option<usize> {
+        self.lookup
+            .get(&(component, String::from(port)))
+            .map(|element| self.nets[*element])
+    }
+
+    /// Whether a net only touches a single port, which is then left unconnected.
+    pub fn is_dangling(&self, net: usize) -> bool {
+        self.sizes[net] == 1
+    }
+
+    /// Every port of a component with its net, `None` when nothing is connected.
+    pub fn ports_of(&self, component: usize) -> Vec<(String, Option<usize>)> {
+        self.owned[component]
+            .iter()
+            .map(|&element| {
+                let net = self.nets[element];
+                (
+                    self.endpoints[element].1.clone(),
+                    (!self.is_dangling(net)).then_some(net),
+                )
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn input_ports(component: &Component) -> impl Iterator<Item = &Port> {
+    component.ports.iter().filter(|port| port.reads())
+}
+
+/// Whether the gate inverts its `input`th input, from Logisim's `negate0`, `negate1`, ...
+pub(crate) fn is_negated(component: &Component, input: usize) -> bool {
+    component
+        .attributes
+        .get(&format!("negate{}", input))
+        .is_some_and(|negate| negate == "true")
+}
+
+/// Whether an XOR or XNOR gate computes parity rather than "exactly one input is high", which
+/// only differ with more than two inputs.
+pub(crate) fn is_odd_parity(component: &Component) -> bool {
+    component
+        .attributes
+        .get("xor")
+        .is_some_and(|xor| xor == "odd")
+}
+
+pub(crate) fn is_output_pin(component: &Component) -> bool {
+    component.component_type == ComponentType::PIN
+        && component
+            .attributes
+            .get("output")
+            .is_some_and(|output| output == "true")
+}
+
+pub(crate) fn is_input_pin(component: &Component) -> bool {
+    component.component_type == ComponentType::PIN && !is_output_pin(component)
+}
+
+/// Whether `name` is the id of `pin` or its name, see `port_name`. Pins sharing a label are
+/// only found by their ids.
+pub(crate) fn pin_matches(components: &[Component], pin: &Component, name: &str) -> bool {
+    pin.id == name || port_name(components, pin) == name
+}
+
+/// A circuit as the HDL backends see it, with its ports and nets named and its components
+/// resolved to the nets they are on, so `to_verilog` and `to_vhdl` only differ in syntax.
+pub(crate) struct Structure<'a> {
+    pub nets: Nets,
+    /// Ports in component order, named like subcircuit ports, with whether they are outputs
+    pub ports: Vec<(String, bool)>,
+    pub parts: Vec<Part<'a>>,
+    /// Nets some part is on that are not named after a pin, so they need declaring
+    pub signals: Vec<usize>,
+}
+
+pub(crate) enum Part<'a> {
+    /// A pin on a net not named after it, which has to be tied to it: the net drives an output
+    /// pin, and an input pin drives the net
+    Pin {
+        port: String,
+        net: usize,
+        output: bool,
+    },
+    /// A subcircuit instance with the net on each of its ports, `None` where nothing is
+    /// connected
+    Instance {
+        component: &'a Component,
+        circuit: &'a str,
+        ports: Vec<(String, Option<usize>)>,
+    },
+    /// A gate with the net it drives and its connected inputs
+    Gate {
+        component: &'a Component,
+        gate: &'a ComponentType,
+        output: usize,
+        inputs: Vec<GateInput>,
+    },
+}
+
+pub(crate) struct GateInput {
+    /// Position among the inputs of the gate, counting unconnected ones
+    pub index: usize,
+    pub net: usize,
+    pub negated: bool,
+}
+
+impl Structure<'_> {
+    pub fn new(circuit: &Circuit) -> Structure<'_> {
+        let nets = Nets::new(circuit);
+        let mut parts: Vec<Part> = Vec::new();
+        let mut used = vec![false; nets.names.len()];
+
+        let ports = circuit
+            .components
+            .iter()
+            .filter(|component| component.component_type == ComponentType::PIN)
+            .map(|pin| (port_name(&circuit.components, pin), is_output_pin(pin)))
+            .collect();
+
+        for (index, component) in circuit.components.iter().enumerate() {
+            match &component.component_type {
+                ComponentType::PIN => {
+                    // The net is named after the first pin on it, the others are tied to it
+                    let port = port_name(&circuit.components, component);
+                    let output = is_output_pin(component);
+                    let Some(net) = nets.net(index, if output { "in" } else { "out" }) else {
+                        continue;
+                    };
+
+                    if nets.names[net] != port {
+                        used[net] = true;
+                        parts.push(Part::Pin { port, net, output });
+                    }
+                }
+                ComponentType::Subcircuit(subcircuit) => {
+                    let ports = nets.ports_of(index);
+                    for net in ports.iter().filter_map(|(_, net)| *net) {
+                        used[net] = true;
+                    }
+
+                    parts.push(Part::Instance {
+                        component,
+                        circuit: subcircuit,
+                        ports,
+                    });
+                }
+                gate => {
+                    let output = component
+                        .ports
+                        .iter()
+                        .find(|port| port.drives())
+                        .and_then(|port| nets.net(index, &port.name));
+                    let Some(output) = output else {
+                        continue;
+                    };
+                    used[output] = true;
+
+                    let mut inputs: Vec<GateInput> = Vec::new();
+                    for (input, port) in input_ports(component).enumerate() {
+                        let Some(net) = nets.net(index, &port.name) else {
+                            continue;
+                        };
+                        if nets.is_dangling(net) {
+                            continue;
+                        }
+
+                        used[net] = true;
+                        inputs.push(GateInput {
+                            index: input,
+                            net,
+                            negated: is_negated(component, input),
+                        });
+                    }
+
+                    parts.push(Part::Gate {
+                        component,
+                        gate,
+                        output,
+                        inputs,
+                    });
+                }
+            }
+        }
+
+        let signals = (0..nets.names.len())
+            .filter(|net| used[*net] && !nets.ports[*net])
+            .collect();
+
+        Structure {
+            nets,
+            ports,
+            parts,
+            signals,
+        }
+    }
+}
+
+/// Logisim's default XOR, high when exactly one input is rather than an odd number of them, as
+/// a sum of products: a term per input, with that input high and every other one low. Each
+/// literal is an input index and whether it is complemented.
+pub(crate) fn exactly_one(inputs: usize) -> Vec<Vec<(usize, bool)>> {
+    (0..inputs)
+        .map(|high| (0..inputs).map(|input| (input, input != high)).collect())
+        .collect()
+}
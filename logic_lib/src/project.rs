@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+use crate::circuit::Circuit;
+
+/// Every circuit of a Logisim project, keyed by name.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Project {
+    pub circuits: BTreeMap<String, Circuit>,
+    /// Name of the circuit Logisim opens first, from the `<main>` element.
+    pub main: String,
+    pub options: Options,
+}
+
+/// Simulation settings from the project's `<options>` element.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Options {
+    /// `gateUndefined`
+    pub gate_undefined: GateUndefined,
+    /// `simlimit`, the number of propagation steps after which a circuit is said to oscillate
+    pub sim_limit: usize,
+    /// `simrand`, how much random jitter is added to gate delays; 0 for none
+    pub sim_rand: u64,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            gate_undefined: GateUndefined::default(),
+            sim_limit: 1000,
+            sim_rand: 0,
+        }
+    }
+}
+
+/// What a gate outputs when some of its inputs are floating.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum GateUndefined {
+    /// Floating inputs are left out, as if the gate did not have them
+    #[default]
+    Ignore,
+    /// Any floating input makes the output unknown
+    Error,
+}
+
+impl Project {
+    pub fn circuit(&self, name: &str) -> Option<&Circuit> {
+        self.circuits.get(name)
+    }
+
+    pub fn circuit_mut(&mut self, name: &str) -> Option<&mut Circuit> {
+        self.circuits.get_mut(name)
+    }
+
+    pub fn main_circuit(&self) -> Option<&Circuit> {
+        self.circuits.get(&self.main)
+    }
+
+    /// Transpiles every circuit into its own `.logic` module, keyed by the circuit name.
+    pub fn to_logic_modules(&self) -> BTreeMap<String, String> {
+        self.circuits
+            .iter()
+            .map(|(name, circuit)| (name.clone(), circuit.to_logic_code()))
+            .collect()
+    }
+}
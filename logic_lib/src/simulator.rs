@@ -0,0 +1,591 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, BinaryHeap, HashMap, VecDeque},
+    fmt,
+};
+
+use crate::appearance::port_name;
+use crate::circuit::Circuit;
+use crate::error::SimulationError;
+use crate::netlist::{
+    is_input_pin, is_negated, is_odd_parity, is_output_pin, pin_matches, Netlist,
+};
+use crate::project::{GateUndefined, Options};
+use crate::types::{Component, ComponentType};
+
+/// The value of a signal, as Logisim shows it on a single-bit wire.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum Value {
+    Zero,
+    One,
+    /// Driven, but not to a known level, e.g. a latch nobody has set yet or two outputs
+    /// disagreeing (`X`)
+    Unknown,
+    /// Not driven at all (`Z`)
+    Floating,
+}
+
+impl Value {
+    /// `Some` for the two defined levels.
+    pub fn to_bool(self) -> Option<bool> {
+        match self {
+            Value::Zero => Some(false),
+            Value::One => Some(true),
+            Value::Unknown | Value::Floating => None,
+        }
+    }
+
+    /// The value of a wire that both `self` and `other` drive.
+    pub fn resolve(self, other: Value) -> Value {
+        match (self, other) {
+            (Value::Floating, value) | (value, Value::Floating) => value,
+            (a, b) if a == b => a,
+            _ => Value::Unknown,
+        }
+    }
+}
+
+impl std::ops::Not for Value {
+    type Output = Value;
+
+    fn not(self) -> Value {
+        match self {
+            Value::Zero => Value::One,
+            Value::One => Value::Zero,
+            Value::Unknown | Value::Floating => Value::Unknown,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Value {
+        if value {
+            Value::One
+        } else {
+            Value::Zero
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Zero => write!(f, "0"),
+            Value::One => write!(f, "1"),
+            Value::Unknown => write!(f, "X"),
+            Value::Floating => write!(f, "Z"),
+        }
+    }
+}
+
+/// How a call to `Simulator::run` ended.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Outcome {
+    /// No value changes anymore
+    Stable { steps: usize },
+    /// `run_until` reached its time with changes still on their way
+    Pending { steps: usize },
+    /// Values were still changing after `simlimit` steps
+    Oscillation {
+        steps: usize,
+        /// Ids of the components whose value changed during the last steps
+        components: Vec<String>,
+    },
+}
+
+impl Outcome {
+    pub fn is_stable(&self) -> bool {
+        matches!(self, Outcome::Stable { .. })
+    }
+}
+
+/// How a gate output reacts to input changes that come faster than its delay.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum DelayModel {
+    /// Every change comes out, `delay` later
+    #[default]
+    Transport,
+    /// A change cancels the ones still on their way, so pulses shorter than the delay are
+    /// swallowed
+    Inertial,
+}
+
+/// A change of a component's value, due at `time`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+struct Event {
+    time: u64,
+    /// Keeps events due at the same time in the order they were scheduled
+    order: u64,
+    component: usize,
+    value: Value,
+    /// Events scheduled before the component's version last changed are cancelled
+    version: u64,
+}
+
+impl Ord for Event {
+    fn cmp(&self, other: &Event) -> Ordering {
+        (self.time, self.order).cmp(&(other.time, other.order))
+    }
+}
+
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Event) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Xorshift generator for the `simrand` jitter, so a seed always gives the same delays.
+#[derive(Clone)]
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> XorShift {
+        // Xorshift never leaves zero
+        XorShift(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// An event-driven simulation of a gate-level circuit.
+///
+/// Every component has a single output value: the output of a gate, the value an input pin is
+/// set to, or the value an output pin reads. When a value changes, the components reading it are
+/// queued to be evaluated again, so feedback loops such as latches keep their state. Gates start
+/// out `Unknown`, so a latch stays `Unknown` until it is set or reset.
+///
+/// Like in Logisim, every gate takes one unit of time to react by default, and pins react at
+/// once: all the components queued at a time are evaluated with the values from before it,
+/// which is what makes a NOR latch released from both inputs at once oscillate. Delays can be
+/// changed per component type with `with_delay` and per component with a `delay` attribute.
+#[derive(Clone)]
+pub struct Simulator {
+    components: Vec<Component>,
+    /// For every component and every one of its inputs, the components driving it
+    inputs: Vec<Vec<Vec<usize>>>,
+    /// For every component, the components reading its value
+    fanout: Vec<Vec<usize>>,
+    values: Vec<Value>,
+    options: Options,
+    queue: VecDeque<usize>,
+    queued: Vec<bool>,
+    /// For every component, the step of the last `run` in which its value changed
+    changed_at: Vec<Option<usize>>,
+    time: u64,
+    events: BinaryHeap<Reverse<Event>>,
+    scheduled: u64,
+    versions: Vec<u64>,
+    /// For every component, its value once the events on their way have happened
+    projected: Vec<Value>,
+    /// Delays given by `delay` attributes
+    own_delays: Vec<Option<u64>>,
+    type_delays: HashMap<ComponentType, u64>,
+    delay_model: DelayModel,
+    rng: XorShift,
+}
+
+impl Simulator {
+    /// Prepares `circuit` for simulation with every input pin low and the default options.
+    pub fn new(circuit: &Circuit) -> Result<Simulator, SimulationError> {
+        let Netlist {
+            components,
+            inputs,
+            fanout,
+        } = Netlist::new(circuit)?;
+
+        let mut own_delays: Vec<Option<u64>> = Vec::new();
+        for component in &components {
+            own_delays.push(match component.attributes.get("delay") {
+                Some(delay) => {
+                    Some(
+                        delay
+                            .parse::<u64>()
+                            .map_err(|_| SimulationError::InvalidDelay {
+                                component: component.id.clone(),
+                                value: delay.clone(),
+                            })?,
+                    )
+                }
+                None => None,
+            });
+        }
+
+        let values: Vec<Value> = components
+            .iter()
+            .map(|component| {
+                if is_input_pin(component) {
+                    Value::Zero
+                } else {
+                    Value::Unknown
+                }
+            })
+            .collect();
+
+        let mut simulator = Simulator {
+            projected: values.clone(),
+            values,
+            options: Options::default(),
+            queued: vec![false; components.len()],
+            changed_at: vec![None; components.len()],
+            queue: VecDeque::new(),
+            time: 0,
+            events: BinaryHeap::new(),
+            scheduled: 0,
+            versions: vec![0; components.len()],
+            own_delays,
+            type_delays: HashMap::new(),
+            delay_model: DelayModel::default(),
+            rng: XorShift::new(0),
+            components,
+            inputs,
+            fanout,
+        };
+
+        for index in 0..simulator.components.len() {
+            if !is_input_pin(&simulator.components[index]) {
+                simulator.schedule(index);
+            }
+        }
+
+        Ok(simulator)
+    }
+
+    /// Uses the project's options, e.g. `gateUndefined` and `simlimit`, instead of Logisim's
+    /// defaults.
+    pub fn with_options(mut self, options: &Options) -> Simulator {
+        self.options = options.clone();
+        self
+    }
+
+    /// Sets the delay of every component of the given type without a `delay` attribute.
+    pub fn with_delay(mut self, component_type: ComponentType, delay: u64) -> Simulator {
+        self.type_delays.insert(component_type, delay);
+        self
+    }
+
+    pub fn with_delay_model(mut self, delay_model: DelayModel) -> Simulator {
+        self.delay_model = delay_model;
+        self
+    }
+
+    /// Seeds the jitter added to every delay when the `simrand` option is set.
+    pub fn with_seed(mut self, seed: u64) -> Simulator {
+        self.rng = XorShift::new(seed);
+        self
+    }
+
+    /// The current simulated time.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Sets the input pin with the given name or id, see `input_names`. The change reaches the rest of the
+    /// circuit on the next `run`. Only pins with the `tristate` attribute can be left floating,
+    /// and no pin can be set to `Unknown`.
+    pub fn set_input(&mut self, pin: &str, value: impl Into<Value>) -> Result<(), SimulationError> {
+        let value = value.into();
+        let components = &self.components;
+        let index = components
+            .iter()
+            .position(|component| {
+                is_input_pin(component) && pin_matches(components, component, pin)
+            })
+            .ok_or_else(|| SimulationError::UnknownPin(String::from(pin)))?;
+
+        let tristate = self.components[index]
+            .attributes
+            .get("tristate")
+            .is_some_and(|tristate| tristate == "true");
+        if value == Value::Unknown || (value == Value::Floating && !tristate) {
+            return Err(SimulationError::InvalidValue {
+                pin: String::from(pin),
+                value,
+            });
+        }
+
+        if self.values[index] != value {
+            self.values[index] = value;
+            self.projected[index] = value;
+            self.schedule_fanout(index);
+        }
+
+        Ok(())
+    }
+
+    /// Propagates changes until no value changes anymore, or until `simlimit` steps (distinct
+    /// times at which values change) have passed. An oscillating circuit picks up where it
+    /// stopped on the next call.
+    pub fn run(&mut self) -> Outcome {
+        self.propagate(None)
+    }
+
+    /// Like `run`, but stops once the simulated time reaches `time`, so inputs can change while
+    /// earlier changes are still on their way.
+    pub fn run_until(&mut self, time: u64) -> Outcome {
+        self.propagate(Some(time))
+    }
+
+    fn propagate(&mut self, until: Option<u64>) -> Outcome {
+        let mut steps = 0;
+        self.changed_at.fill(None);
+
+        loop {
+            let queued: Vec<usize> = self.queue.drain(..).collect();
+            for index in queued {
+                self.queued[index] = false;
+
+                let value = self.evaluate(index);
+                self.schedule_change(index, value);
+            }
+
+            // Events cancelled by an inertial delay do not count as steps
+            while let Some(Reverse(event)) = self.events.peek() {
+                if event.version == self.versions[event.component] {
+                    break;
+                }
+                self.events.pop();
+            }
+
+            let Some(Reverse(next)) = self.events.peek() else {
+                if let Some(until) = until {
+                    self.time = self.time.max(until);
+                }
+                return Outcome::Stable { steps };
+            };
+            let time = next.time;
+
+            if let Some(until) = until.filter(|until| time > *until) {
+                self.time = self.time.max(until);
+                return Outcome::Pending { steps };
+            }
+
+            if steps == self.options.sim_limit {
+                // A ring of n gates changes each of them once every n steps
+                let window = self.components.len().min(steps);
+                let components = self
+                    .components
+                    .iter()
+                    .zip(&self.changed_at)
+                    .filter(|(_, changed_at)| changed_at.is_some_and(|at| at >= steps - window))
+                    .map(|(component, _)| component.id.clone())
+                    .collect();
+
+                return Outcome::Oscillation { steps, components };
+            }
+
+            self.time = time;
+            while self
+                .events
+                .peek()
+                .is_some_and(|Reverse(event)| event.time == time)
+            {
+                let Some(Reverse(event)) = self.events.pop() else {
+                    break;
+                };
+                let index = event.component;
+
+                if event.version == self.versions[index] && event.value != self.values[index] {
+                    self.values[index] = event.value;
+                    self.changed_at[index] = Some(steps);
+                    self.schedule_fanout(index);
+                }
+            }
+
+            steps += 1;
+        }
+    }
+
+    /// Schedules the component's value to become `value` after its delay.
+    fn schedule_change(&mut self, index: usize, value: Value) {
+        if value == self.projected[index] {
+            return;
+        }
+
+        if self.delay_model == DelayModel::Inertial {
+            // Whatever was still on its way is replaced
+            self.versions[index] += 1;
+            self.projected[index] = self.values[index];
+
+            if value == self.values[index] {
+                return;
+            }
+        }
+
+        let delay = self.delay(index);
+        self.events.push(Reverse(Event {
+            time: self.time + delay,
+            order: self.scheduled,
+            component: index,
+            value,
+            version: self.versions[index],
+        }));
+        self.scheduled += 1;
+        self.projected[index] = value;
+    }
+
+    /// The component's delay, stretched by `simrand` with some random jitter added: equal
+    /// delays then come out in a random order, while longer ones stay longer.
+    fn delay(&mut self, index: usize) -> u64 {
+        let component_type = &self.components[index].component_type;
+        let delay = self.own_delays[index]
+            .or_else(|| self.type_delays.get(component_type).copied())
+            .unwrap_or(match component_type {
+                ComponentType::PIN => 0,
+                _ => 1,
+            });
+
+        match self.options.sim_rand {
+            0 => delay,
+            sim_rand => delay * sim_rand + self.rng.next() % sim_rand,
+        }
+    }
+
+    /// The output value of the component with the given id.
+    pub fn value(&self, id: &str) -> Option<Value> {
+        self.components
+            .iter()
+            .position(|component| component.id == id)
+            .map(|index| self.values[index])
+    }
+
+    /// The value read by the output pin with the given name or id, see `output_names`.
+    pub fn output(&self, pin: &str) -> Option<Value> {
+        let components = &self.components;
+        components
+            .iter()
+            .position(|component| {
+                is_output_pin(component) && pin_matches(components, component, pin)
+            })
+            .map(|index| self.values[index])
+    }
+
+    /// Names of the input pins, in circuit order: their labels, or their ids where a label is
+    /// missing, shared with another pin or not an identifier.
+    pub fn input_names(&self) -> Vec<String> {
+        self.components
+            .iter()
+            .filter(|component| is_input_pin(component))
+            .map(|component| port_name(&self.components, component))
+            .collect()
+    }
+
+    /// Names of the output pins, in circuit order, like `input_names`.
+    pub fn output_names(&self) -> Vec<String> {
+        self.components
+            .iter()
+            .filter(|component| is_output_pin(component))
+            .map(|component| port_name(&self.components, component))
+            .collect()
+    }
+
+    /// The value of every output pin, keyed by its name.
+    pub fn outputs(&self) -> BTreeMap<String, Value> {
+        self.components
+            .iter()
+            .zip(&self.values)
+            .filter(|(component, _)| is_output_pin(component))
+            .map(|(component, value)| (port_name(&self.components, component), *value))
+            .collect()
+    }
+
+    fn schedule(&mut self, index: usize) {
+        if !self.queued[index] {
+            self.queued[index] = true;
+            self.queue.push_back(index);
+        }
+    }
+
+    fn schedule_fanout(&mut self, index: usize) {
+        for sink in self.fanout[index].clone() {
+            self.schedule(sink);
+        }
+    }
+
+    fn evaluate(&self, index: usize) -> Value {
+        let component = &self.components[index];
+        let inputs: Vec<Value> = self.inputs[index]
+            .iter()
+            .enumerate()
+            .map(|(input, drivers)| {
+                let value = drivers.iter().fold(Value::Floating, |value, &driver| {
+                    value.resolve(self.values[driver])
+                });
+
+                if is_negated(component, input) && value != Value::Floating {
+                    !value
+                } else {
+                    value
+                }
+            })
+            .collect();
+
+        let kind = match component.component_type {
+            // Output pins show what they read, floating or not
+            ComponentType::PIN => {
+                return inputs.first().copied().unwrap_or(self.values[index]);
+            }
+            ComponentType::NOT => return !inputs[0],
+            ComponentType::Subcircuit(_) => unreachable!("subcircuits are rejected by `new`"),
+            ref kind => kind,
+        };
+
+        let inputs: Vec<Value> = match self.options.gate_undefined {
+            GateUndefined::Ignore => inputs
+                .into_iter()
+                .filter(|input| *input != Value::Floating)
+                .collect(),
+            GateUndefined::Error => inputs
+                .into_iter()
+                .map(|input| match input {
+                    Value::Floating => Value::Unknown,
+                    input => input,
+                })
+                .collect(),
+        };
+
+        if inputs.is_empty() {
+            return Value::Unknown;
+        }
+
+        let ones = inputs.iter().filter(|input| **input == Value::One).count();
+        let unknown = inputs.contains(&Value::Unknown);
+        let odd = is_odd_parity(component);
+
+        let and = if inputs.contains(&Value::Zero) {
+            Value::Zero
+        } else if unknown {
+            Value::Unknown
+        } else {
+            Value::One
+        };
+        let or = if ones > 0 {
+            Value::One
+        } else if unknown {
+            Value::Unknown
+        } else {
+            Value::Zero
+        };
+        let xor = if unknown {
+            Value::Unknown
+        } else if odd {
+            Value::from(ones % 2 == 1)
+        } else {
+            Value::from(ones == 1)
+        };
+
+        match kind {
+            ComponentType::AND => and,
+            ComponentType::NAND => !and,
+            ComponentType::OR => or,
+            ComponentType::NOR => !or,
+            ComponentType::XOR => xor,
+            ComponentType::XNOR => !xor,
+            _ => unreachable!("pins and NOT gates are handled above"),
+        }
+    }
+}
@@ -0,0 +1,430 @@
+use std::collections::HashSet;
+
+use crate::circuit::Circuit;
+use crate::netlist::{is_negated, is_output_pin};
+use crate::types::{Component, ComponentType, Coordinate};
+
+/// Logisim's colour for wires carrying a known value.
+const WIRE_COLOR: &str = "#006400";
+/// Space left around the drawing.
+const MARGIN: i64 = 10;
+/// Rough width of a character of a label, to leave room for it.
+const CHAR_WIDTH: i64 = 7;
+
+impl Circuit {
+    /// Draws the circuit as an SVG picture laid out like the Logisim canvas: wires with a dot
+    /// where three or more meet, gates in their usual shapes, square input pins, rounded output
+    /// pins, subcircuits as boxes spanning their ports, and labels.
+    ///
+    /// Positions come from the `.circ` file; circuits read from `.logic` code have none and all
+    /// end up on top of each other.
+    pub fn to_svg(&self) -> String {
+        let mut bounds = Bounds::default();
+        let mut shapes: Vec<String> = Vec::new();
+        let mut texts: Vec<String> = Vec::new();
+
+        for wire in &self.wires {
+            bounds.include(point(wire.from));
+            bounds.include(point(wire.to));
+        }
+
+        for component in &self.components {
+            let (x, y) = point(component.loc);
+            let facing = component.facing();
+
+            match &component.component_type {
+                ComponentType::PIN => {
+                    let (left, top, right, bottom) = rotate_box((-20, -10, 0, 10), facing);
+                    bounds.include((x + left, y + top));
+                    bounds.include((x + right, y + bottom));
+
+                    // Logisim draws output pins with round corners
+                    let corners = if is_output_pin(component) {
+                        " rx=\"6\""
+                    } else {
+                        ""
+                    };
+                    shapes.push(format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"20\" height=\"20\"{}/>",
+                        x + left,
+                        y + top,
+                        corners
+                    ));
+
+                    // Labels go on the side away from the wire
+                    if let Some(label) = label(component) {
+                        let (dx, dy) = rotate((-24, 0), facing);
+                        let (anchor, baseline) = match facing {
+                            "west" => ("start", 4),
+                            "north" => ("middle", 12),
+                            "south" => ("middle", 0),
+                            _ => ("end", 4),
+                        };
+                        texts.push(text(x + dx, y + dy + baseline, anchor, label));
+                        bounds.include_text(x + dx, y + dy + baseline, anchor, label);
+                    }
+                }
+                ComponentType::Subcircuit(circuit) => {
+                    let (mut left, mut top, mut right, mut bottom) = port_box(component);
+                    if right - left < 20 {
+                        (left, right) = ((left + right) / 2 - 10, (left + right) / 2 + 10);
+                    }
+                    if bottom - top < 20 {
+                        (top, bottom) = ((top + bottom) / 2 - 10, (top + bottom) / 2 + 10);
+                    }
+                    bounds.include((left, top));
+                    bounds.include((right, bottom));
+
+                    shapes.push(format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                        left,
+                        top,
+                        right - left,
+                        bottom - top
+                    ));
+                    // The name is wider than most boxes, it goes under the box
+                    let middle = (left + right) / 2;
+                    texts.push(text(middle, bottom + 14, "middle", circuit));
+                    bounds.include_text(middle, bottom + 14, "middle", circuit);
+                    if let Some(label) = label(component) {
+                        texts.push(text(middle, top - 4, "middle", label));
+                        bounds.include_text(middle, top - 4, "middle", label);
+                    }
+                }
+                gate => {
+                    let depth = gate.depth().expect("every gate has a shape");
+                    // The body grows to fit the inputs, which are 10 apart
+                    let half = component
+                        .ports
+                        .iter()
+                        .filter(|port| port.reads())
+                        .map(|port| {
+                            let (px, py) = point(port.loc);
+                            match facing {
+                                "north" | "south" => (px - x).abs(),
+                                _ => (py - y).abs(),
+                            }
+                        })
+                        .max()
+                        .map_or(15, |offset| (offset + 5).max(15));
+
+                    let (left, top, right, bottom) = rotate_box((-depth, -half, 0, half), facing);
+                    bounds.include((x + left, y + top));
+                    bounds.include((x + right, y + bottom));
+
+                    let mut parts = gate_shape(gate, depth, half);
+                    for (input, port) in component
+                        .ports
+                        .iter()
+                        .filter(|port| port.reads())
+                        .enumerate()
+                    {
+                        if is_negated(component, input) {
+                            let (px, py) = point(port.loc);
+                            let (dx, dy) = unrotate((px - x, py - y), facing);
+                            parts.push(format!(
+                                "<circle cx=\"{}\" cy=\"{}\" r=\"4\"/>",
+                                dx + 4,
+                                dy
+                            ));
+                        }
+                    }
+
+                    let rotation = match facing {
+                        "south" => " rotate(90)",
+                        "west" => " rotate(180)",
+                        "north" => " rotate(270)",
+                        _ => "",
+                    };
+                    shapes.push(format!(
+                        "<g transform=\"translate({},{}){}\">{}</g>",
+                        x,
+                        y,
+                        rotation,
+                        parts.join("")
+                    ));
+
+                    if let Some(label) = label(component) {
+                        let middle = x + (left + right) / 2;
+                        texts.push(text(middle, y + top - 4, "middle", label));
+                        bounds.include_text(middle, y + top - 4, "middle", label);
+                    }
+                }
+            }
+        }
+
+        let (left, top, width, height) = bounds.view_box();
+        let mut svg = String::new();
+        svg.push_str(
+            format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+                width, height, left, top, width, height
+            )
+            .as_str(),
+        );
+        svg.push_str(
+            format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+                left, top, width, height
+            )
+            .as_str(),
+        );
+
+        svg.push_str(
+            format!(
+                "  <g stroke=\"{}\" stroke-width=\"2\" stroke-linecap=\"round\">\n",
+                WIRE_COLOR
+            )
+            .as_str(),
+        );
+        for wire in &self.wires {
+            let ((x1, y1), (x2, y2)) = (point(wire.from), point(wire.to));
+            svg.push_str(
+                format!(
+                    "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
+                    x1, y1, x2, y2
+                )
+                .as_str(),
+            );
+        }
+        svg.push_str("  </g>\n");
+
+        let junctions = junctions(self);
+        if !junctions.is_empty() {
+            svg.push_str(format!("  <g fill=\"{}\">\n", WIRE_COLOR).as_str());
+            for (x, y) in junctions {
+                svg.push_str(format!("    <circle cx=\"{}\" cy=\"{}\" r=\"4\"/>\n", x, y).as_str());
+            }
+            svg.push_str("  </g>\n");
+        }
+
+        svg.push_str("  <g fill=\"white\" stroke=\"black\" stroke-width=\"2\">\n");
+        for shape in &shapes {
+            svg.push_str(format!("    {}\n", shape).as_str());
+        }
+        svg.push_str("  </g>\n");
+
+        if !texts.is_empty() {
+            svg.push_str("  <g font-family=\"sans-serif\" font-size=\"12\">\n");
+            for text in &texts {
+                svg.push_str(format!("    {}\n", text).as_str());
+            }
+            svg.push_str("  </g>\n");
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// The outline of a gate facing east with its output at the origin: a body 30 wide, behind it
+/// the extra curve of XOR gates and in front of it the bubble of inverting gates.
+fn gate_shape(gate: &ComponentType, depth: i64, half: i64) -> Vec<String> {
+    let inverted = matches!(
+        gate,
+        ComponentType::NAND | ComponentType::NOR | ComponentType::XNOR | ComponentType::NOT
+    );
+    let front = if inverted { -10 } else { 0 };
+    let back = if matches!(gate, ComponentType::XOR | ComponentType::XNOR) {
+        -depth + 10
+    } else {
+        -depth
+    };
+
+    let mut parts: Vec<String> = Vec::new();
+    match gate {
+        ComponentType::NOT => parts.push(format!(
+            "<path d=\"M{},-7 L{},0 L{},7 Z\"/>",
+            back, front, back
+        )),
+        ComponentType::AND | ComponentType::NAND => parts.push(format!(
+            "<path d=\"M{},{} H{} A15,{} 0 0 1 {},{} H{} Z\"/>",
+            back,
+            -half,
+            front - 15,
+            half,
+            front - 15,
+            half,
+            back
+        )),
+        _ => {
+            parts.push(format!(
+                "<path d=\"M{},{} Q{},{} {},0 Q{},{} {},{} Q{},0 {},{} Z\"/>",
+                back,
+                -half,
+                front - 10,
+                -half,
+                front,
+                front - 10,
+                half,
+                back,
+                half,
+                back + 8,
+                back,
+                -half
+            ));
+            if back != -depth {
+                parts.push(format!(
+                    "<path d=\"M{},{} Q{},0 {},{}\" fill=\"none\"/>",
+                    -depth,
+                    -half,
+                    -depth + 8,
+                    -depth,
+                    half
+                ));
+            }
+        }
+    }
+    if inverted {
+        parts.push(String::from("<circle cx=\"-5\" cy=\"0\" r=\"5\"/>"));
+    }
+
+    parts
+}
+
+/// Points where three or more wire segments meet once wires are split where others end on
+/// them, in the order wire ends and ports first mention them.
+fn junctions(circuit: &Circuit) -> Vec<(i64, i64)> {
+    let graph = circuit.wire_graph();
+    let mut seen: HashSet<Coordinate> = HashSet::new();
+
+    circuit
+        .wires
+        .iter()
+        .flat_map(|wire| [wire.from, wire.to])
+        .chain(
+            circuit
+                .components
+                .iter()
+                .flat_map(|component| &component.ports)
+                .map(|port| port.loc),
+        )
+        .filter(|point| seen.insert(*point))
+        .filter(|point| graph.get(point).is_some_and(|segments| segments.len() >= 3))
+        .map(point)
+        .collect()
+}
+
+#[derive(Default)]
+struct Bounds {
+    corners: Option<(i64, i64, i64, i64)>,
+}
+
+impl Bounds {
+    fn include(&mut self, (x, y): (i64, i64)) {
+        self.corners = Some(match self.corners {
+            Some((left, top, right, bottom)) => {
+                (left.min(x), top.min(y), right.max(x), bottom.max(y))
+            }
+            None => (x, y, x, y),
+        });
+    }
+
+    /// Leaves room for a line of text anchored at a point on its baseline.
+    fn include_text(&mut self, x: i64, y: i64, anchor: &str, text: &str) {
+        let width = text.chars().count() as i64 * CHAR_WIDTH;
+        let (left, right) = match anchor {
+            "start" => (x, x + width),
+            "end" => (x - width, x),
+            _ => (x - width / 2, x + width / 2),
+        };
+        self.include((left, y - 12));
+        self.include((right, y + 3));
+    }
+
+    /// Left, top, width and height of the drawing with a margin around it.
+    fn view_box(&self) -> (i64, i64, i64, i64) {
+        let (left, top, right, bottom) = self.corners.unwrap_or_default();
+        (
+            left - MARGIN,
+            top - MARGIN,
+            right - left + 2 * MARGIN,
+            bottom - top + 2 * MARGIN,
+        )
+    }
+}
+
+/// The left, top, right and bottom edges of the box drawn around a component: its ports sit
+/// on the sides, and the box reaches 10 past the first and last one.
+pub(crate) fn port_box(component: &Component) -> (i64, i64, i64, i64) {
+    let (x, y) = point(component.loc);
+    let (mut left, mut top, mut right, mut bottom) = (x, y, x, y);
+    for port in &component.ports {
+        let (px, py) = point(port.loc);
+        left = left.min(px);
+        top = top.min(py);
+        right = right.max(px);
+        bottom = bottom.max(py);
+    }
+
+    if matches!(component.facing(), "north" | "south") {
+        (left, right) = (left - 10, right + 10);
+    } else {
+        (top, bottom) = (top - 10, bottom + 10);
+    }
+
+    (left, top, right, bottom)
+}
+
+pub(crate) fn point(coordinate: Coordinate) -> (i64, i64) {
+    (i64::from(coordinate.x), i64::from(coordinate.y))
+}
+
+/// Turns an offset from an east-facing layout to face `facing`.
+fn rotate((dx, dy): (i64, i64), facing: &str) -> (i64, i64) {
+    match facing {
+        "west" => (-dx, -dy),
+        "north" => (dy, -dx),
+        "south" => (-dy, dx),
+        _ => (dx, dy),
+    }
+}
+
+/// Turns an offset back into the east-facing layout.
+fn unrotate(offset: (i64, i64), facing: &str) -> (i64, i64) {
+    match facing {
+        "north" => rotate(offset, "south"),
+        "south" => rotate(offset, "north"),
+        _ => rotate(offset, facing),
+    }
+}
+
+/// Turns a box given by its left, top, right and bottom edges around the origin.
+fn rotate_box(
+    (left, top, right, bottom): (i64, i64, i64, i64),
+    facing: &str,
+) -> (i64, i64, i64, i64) {
+    let (x1, y1) = rotate((left, top), facing);
+    let (x2, y2) = rotate((right, bottom), facing);
+    (x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2))
+}
+
+pub(crate) fn label(component: &Component) -> Option<&str> {
+    component
+        .attributes
+        .get("label")
+        .map(|label| label.as_str())
+        .filter(|label| !label.is_empty())
+}
+
+fn text(x: i64, y: i64, anchor: &str, content: &str) -> String {
+    let anchor = if anchor == "start" {
+        String::new()
+    } else {
+        format!(" text-anchor=\"{}\"", anchor)
+    };
+    format!(
+        "<text x=\"{}\" y=\"{}\"{}>{}</text>",
+        x,
+        y,
+        anchor,
+        escape(content)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
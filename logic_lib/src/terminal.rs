@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use crate::circuit::Circuit;
+use crate::netlist::{is_negated, is_output_pin};
+use crate::svg::{label, point, port_box};
+use crate::types::ComponentType;
+
+const NORTH: u8 = 1;
+const EAST: u8 = 2;
+const SOUTH: u8 = 4;
+const WEST: u8 = 8;
+
+/// Characters `Circuit::to_terminal` draws with.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+pub enum Charset {
+    /// Box-drawing characters
+    #[default]
+    Unicode,
+    /// `-`, `|` and `+`, for terminals and fonts without box-drawing characters
+    Ascii,
+}
+
+impl Circuit {
+    /// Draws the circuit on a character grid for a terminal, a row per 10 units of the Logisim
+    /// canvas and two columns, as characters are about twice as tall as they are wide.
+    ///
+    /// Wires are lines, gates and subcircuits boxes named after their type with their label
+    /// above, and pins a mark with their label on the side away from the wire. Like
+    /// `Circuit::to_svg`, this needs the positions of a `.circ` file.
+    pub fn to_terminal(&self, charset: Charset) -> String {
+        let mut canvas = Canvas::default();
+
+        for wire in &self.wires {
+            line(
+                &mut canvas.wires,
+                cell(point(wire.from)),
+                cell(point(wire.to)),
+            );
+        }
+
+        for component in &self.components {
+            let (row, column) = cell(point(component.loc));
+            let facing = component.facing();
+
+            if component.component_type == ComponentType::PIN {
+                let (mark, ascii_mark) = if is_output_pin(component) {
+                    ('●', '@')
+                } else {
+                    ('■', '#')
+                };
+                let mark = match charset {
+                    Charset::Unicode => mark,
+                    Charset::Ascii => ascii_mark,
+                };
+                canvas.chars.insert((row, column), mark);
+
+                // Labels go on the side away from the wire
+                if let Some(label) = label(component) {
+                    let width = label.chars().count() as i64;
+                    match facing {
+                        "west" => canvas.text(row, column + 2, label),
+                        "north" => canvas.text(row + 1, column - width / 2, label),
+                        "south" => canvas.text(row - 1, column - width / 2, label),
+                        _ => canvas.text(row, column - 1 - width, label),
+                    }
+                }
+                continue;
+            }
+
+            let (left, top, right, bottom) = port_box(component);
+            let ((top, left), (bottom, right)) = (cell((left, top)), cell((right, bottom)));
+
+            let mut outline: HashMap<(i64, i64), u8> = HashMap::new();
+            line(&mut outline, (top, left), (top, right));
+            line(&mut outline, (top, right), (bottom, right));
+            line(&mut outline, (bottom, right), (bottom, left));
+            line(&mut outline, (bottom, left), (top, left));
+
+            // A stub out of the box where each port is
+            let mut inputs = 0;
+            let mut negated: Vec<(i64, i64)> = Vec::new();
+            for port in &component.ports {
+                let (port_row, port_column) = cell(point(port.loc));
+                let stub = if port_column == left {
+                    WEST
+                } else if port_column == right {
+                    EAST
+                } else if port_row == top {
+                    NORTH
+                } else {
+                    SOUTH
+                };
+                *outline.entry((port_row, port_column)).or_insert(0) |= stub;
+
+                if port.reads() {
+                    if is_negated(component, inputs) {
+                        negated.push((port_row, port_column));
+                    }
+                    inputs += 1;
+                }
+            }
+
+            // The box hides the wires under it
+            for inside_row in top + 1..bottom {
+                for inside_column in left + 1..right {
+                    canvas.chars.insert((inside_row, inside_column), ' ');
+                }
+            }
+            for (position, directions) in outline {
+                canvas
+                    .chars
+                    .insert(position, line_char(directions, charset));
+            }
+            for position in negated {
+                canvas.chars.insert(position, 'o');
+            }
+
+            let name = match &component.component_type {
+                ComponentType::Subcircuit(circuit) => circuit.clone(),
+                gate => format!("{:?}", gate),
+            };
+            let width = (right - left - 1).max(0) as usize;
+            let name: String = name.chars().take(width).collect();
+            let middle = (top + bottom) / 2;
+            canvas.text(
+                middle,
+                left + 1 + (width - name.chars().count()) as i64 / 2,
+                &name,
+            );
+
+            if let Some(label) = label(component) {
+                let width = label.chars().count() as i64;
+                canvas.text(top - 1, (left + right) / 2 - width / 2, label);
+            }
+        }
+
+        canvas.render(charset)
+    }
+}
+
+/// Cells by row and column, wires as the directions they leave a cell in, and characters
+/// drawn over them.
+#[derive(Default)]
+struct Canvas {
+    wires: HashMap<(i64, i64), u8>,
+    chars: HashMap<(i64, i64), char>,
+}
+
+impl Canvas {
+    fn text(&mut self, row: i64, column: i64, text: &str) {
+        for (offset, c) in text.chars().enumerate() {
+            self.chars.insert((row, column + offset as i64), c);
+        }
+    }
+
+    fn render(&self, charset: Charset) -> String {
+        let cells = self.wires.keys().chain(self.chars.keys());
+        let (Some(top), Some(bottom)) = (
+            cells.clone().map(|(row, _)| *row).min(),
+            cells.clone().map(|(row, _)| *row).max(),
+        ) else {
+            return String::new();
+        };
+        let left = cells.clone().map(|(_, column)| *column).min().unwrap_or(0);
+        let right = cells.map(|(_, column)| *column).max().unwrap_or(0);
+
+        let mut text = String::new();
+        for row in top..=bottom {
+            let line: String = (left..=right)
+                .map(|column| match self.chars.get(&(row, column)) {
+                    Some(c) => *c,
+                    None => self
+                        .wires
+                        .get(&(row, column))
+                        .map_or(' ', |directions| line_char(*directions, charset)),
+                })
+                .collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+/// Draws a horizontal or vertical line between two cells.
+fn line(cells: &mut HashMap<(i64, i64), u8>, from: (i64, i64), to: (i64, i64)) {
+    let ((row, column), (to_row, to_column)) = (from.min(to), from.max(to));
+    if row == to_row {
+        for current in column..=to_column {
+            let mut directions = 0;
+            if current > column {
+                directions |= WEST;
+            }
+            if current < to_column {
+                directions |= EAST;
+            }
+            *cells.entry((row, current)).or_insert(0) |= directions;
+        }
+    } else {
+        for current in row..=to_row {
+            let mut directions = 0;
+            if current > row {
+                directions |= NORTH;
+            }
+            if current < to_row {
+                directions |= SOUTH;
+            }
+            *cells.entry((current, column)).or_insert(0) |= directions;
+        }
+    }
+}
+
+/// The cell a point of the canvas falls in, as row and column.
+fn cell((x, y): (i64, i64)) -> (i64, i64) {
+    (y.div_euclid(10), x.div_euclid(5))
+}
+
+/// The character joining lines leaving a cell in `directions`.
+fn line_char(directions: u8, charset: Charset) -> char {
+    let horizontal = directions & (NORTH | SOUTH) == 0;
+    let vertical = directions & (EAST | WEST) == 0;
+
+    match charset {
+        Charset::Ascii if directions == 0 => ' ',
+        Charset::Ascii if horizontal => '-',
+        Charset::Ascii if vertical => '|',
+        Charset::Ascii => '+',
+        Charset::Unicode => match directions {
+            0 => ' ',
+            _ if horizontal => '─',
+            _ if vertical => '│',
+            d if d == EAST | SOUTH => '┌',
+            d if d == SOUTH | WEST => '┐',
+            d if d == NORTH | EAST => '└',
+            d if d == NORTH | WEST => '┘',
+            d if d == NORTH | EAST | SOUTH => '├',
+            d if d == NORTH | SOUTH | WEST => '┤',
+            d if d == EAST | SOUTH | WEST => '┬',
+            d if d == NORTH | EAST | WEST => '┴',
+            _ => '┼',
+        },
+    }
+}
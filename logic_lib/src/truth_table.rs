@@ -0,0 +1,299 @@
+use crate::circuit::Circuit;
+use crate::error::TruthTableError;
+use crate::simulator::{Simulator, Value};
+
+/// Inputs allowed by `TruthTable::new`, which makes tables of up to 65536 rows.
+pub const DEFAULT_MAX_INPUTS: usize = 16;
+
+/// The output pin values of a circuit for every combination of its input pin values.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TruthTable {
+    pub circuit: String,
+    /// Names of the input pins, see `Simulator::input_names`; the first one is the most
+    /// significant bit of the row number
+    pub inputs: Vec<String>,
+    /// Names of the output pins, see `Simulator::output_names`
+    pub outputs: Vec<String>,
+    pub rows: Vec<Row>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Row {
+    pub inputs: Vec<bool>,
+    pub outputs: Vec<Value>,
+}
+
+impl TruthTable {
+    /// Tabulates a circuit with at most `DEFAULT_MAX_INPUTS` inputs.
+    pub fn new(circuit: &Circuit) -> Result<TruthTable, TruthTableError> {
+        TruthTable::with_max_inputs(circuit, DEFAULT_MAX_INPUTS)
+    }
+
+    /// Tabulates the circuit by simulating every combination of its inputs, refusing circuits
+    /// with more than `max_inputs` inputs, or with 64 or more on 64-bit targets.
+    ///
+    /// Every row starts from the state the circuit settles in with all inputs low, so a value a
+    /// circuit remembers, like the state of a latch, shows up as `Unknown` rather than depending
+    /// on the previous row.
+    pub fn with_max_inputs(
+        circuit: &Circuit,
+        max_inputs: usize,
+    ) -> Result<TruthTable, TruthTableError> {
+        let mut initial = Simulator::new(circuit)?;
+        let inputs = initial.input_names();
+        let outputs = initial.output_names();
+
+        // Row numbers are `usize`s, with a bit per input
+        let max_inputs = max_inputs.min(usize::BITS as usize - 1);
+        if inputs.len() > max_inputs {
+            return Err(TruthTableError::TooManyInputs {
+                inputs: inputs.len(),
+                max: max_inputs,
+            });
+        }
+
+        if !initial.run().is_stable() {
+            return Err(TruthTableError::Oscillation(vec![false; inputs.len()]));
+        }
+
+        let mut rows: Vec<Row> = Vec::new();
+        for row in 0..1_usize << inputs.len() {
+            let values: Vec<bool> = (0..inputs.len())
+                .map(|index| row & (1 << (inputs.len() - 1 - index)) != 0)
+                .collect();
+
+            let mut simulator = initial.clone();
+            for (input, value) in inputs.iter().zip(&values) {
+                simulator.set_input(input, *value)?;
+            }
+
+            if !simulator.run().is_stable() {
+                return Err(TruthTableError::Oscillation(values));
+            }
+
+            rows.push(Row {
+                inputs: values,
+                outputs: outputs
+                    .iter()
+                    .map(|output| simulator.output(output).unwrap_or(Value::Unknown))
+                    .collect(),
+            });
+        }
+
+        Ok(TruthTable {
+            circuit: circuit.name.clone(),
+            inputs,
+            outputs,
+            rows,
+        })
+    }
+
+    /// The output values for the given input values, in input order.
+    pub fn lookup(&self, inputs: &[bool]) -> Option<&[Value]> {
+        self.rows
+            .iter()
+            .find(|row| row.inputs == inputs)
+            .map(|row| row.outputs.as_slice())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        let columns = self.inputs.len() + self.outputs.len();
+
+        let header: Vec<String> = self
+            .inputs
+            .iter()
+            .chain(&self.outputs)
+            .map(|name| name.replace('|', "\\|"))
+            .collect();
+        markdown.push_str(format!("| {} |\n", header.join(" | ")).as_str());
+        markdown.push_str(format!("|{}\n", "---|".repeat(columns)).as_str());
+
+        for row in &self.rows {
+            markdown.push_str(format!("| {} |\n", row_cells(row).join(" | ")).as_str());
+        }
+
+        markdown
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+
+        let header: Vec<String> = self
+            .inputs
+            .iter()
+            .chain(&self.outputs)
+            .map(|name| csv_field(name))
+            .collect();
+        csv.push_str(format!("{}\n", header.join(",")).as_str());
+
+        for row in &self.rows {
+            csv.push_str(format!("{}\n", row_cells(row).join(",")).as_str());
+        }
+
+        csv
+    }
+
+    /// Rust code declaring a struct with one field per pin and a constant array holding every
+    /// row. Outputs that are not always `0` or `1` are `Option<bool>`, with `None` for `X` and
+    /// `Z`.
+    pub fn to_rust(&self) -> String {
+        let struct_name = format!("{}Row", upper_camel_case(&self.circuit));
+        let const_name = format!("{}_TRUTH_TABLE", screaming_snake_case(&self.circuit));
+
+        let mut fields: Vec<String> = Vec::new();
+        for name in self.inputs.iter().chain(&self.outputs) {
+            let mut field = field_name(name);
+            while fields.contains(&field) {
+                field.push('_');
+            }
+            fields.push(field);
+        }
+        let (input_fields, output_fields) = fields.split_at(self.inputs.len());
+
+        let optional: Vec<bool> = (0..self.outputs.len())
+            .map(|output| {
+                self.rows
+                    .iter()
+                    .any(|row| row.outputs[output].to_bool().is_none())
+            })
+            .collect();
+
+        let mut code = String::new();
+        code.push_str(format!("/// Truth table of the `{}` circuit.\n", self.circuit).as_str());
+        code.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq)]\n");
+        code.push_str(format!("pub struct {} {{\n", struct_name).as_str());
+        for (field, name) in input_fields.iter().zip(&self.inputs) {
+            code.push_str(format!("    /// Input `{}`\n    pub {}: bool,\n", name, field).as_str());
+        }
+        for ((field, name), optional) in output_fields.iter().zip(&self.outputs).zip(&optional) {
+            let field_type = if *optional { "Option<bool>" } else { "bool" };
+            code.push_str(
+                format!(
+                    "    /// Output `{}`\n    pub {}: {},\n",
+                    name, field, field_type
+                )
+                .as_str(),
+            );
+        }
+        code.push_str("}\n\n");
+
+        code.push_str(
+            format!(
+                "pub const {}: [{}; {}] = [\n",
+                const_name,
+                struct_name,
+                self.rows.len()
+            )
+            .as_str(),
+        );
+        for row in &self.rows {
+            let mut values: Vec<String> = Vec::new();
+
+            for (field, value) in input_fields.iter().zip(&row.inputs) {
+                values.push(format!("{}: {}", field, value));
+            }
+            for ((field, value), optional) in output_fields.iter().zip(&row.outputs).zip(&optional)
+            {
+                let value = match (value.to_bool(), optional) {
+                    (Some(value), false) => value.to_string(),
+                    (Some(value), true) => format!("Some({})", value),
+                    (None, _) => String::from("None"),
+                };
+                values.push(format!("{}: {}", field, value));
+            }
+
+            code.push_str(format!("    {} {{ {} }},\n", struct_name, values.join(", ")).as_str());
+        }
+        code.push_str("];\n");
+
+        code
+    }
+}
+
+fn row_cells(row: &Row) -> Vec<String> {
+    row.inputs
+        .iter()
+        .map(|input| Value::from(*input).to_string())
+        .chain(row.outputs.iter().map(|output| output.to_string()))
+        .collect()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+/// Splits `name` into lowercase words at anything that is not a letter or digit, and where a
+/// lowercase letter is followed by an uppercase one.
+fn words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut previous_lowercase = false;
+
+    for c in name.chars() {
+        let boundary = !c.is_ascii_alphanumeric() || (c.is_ascii_uppercase() && previous_lowercase);
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        if c.is_ascii_alphanumeric() {
+            word.push(c.to_ascii_lowercase());
+        }
+        previous_lowercase = c.is_ascii_lowercase() || c.is_ascii_digit();
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+fn upper_camel_case(name: &str) -> String {
+    let name: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    match name.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => name,
+        _ => format!("Circuit{}", name),
+    }
+}
+
+fn screaming_snake_case(name: &str) -> String {
+    let name = words(name).join("_").to_ascii_uppercase();
+
+    match name.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => name,
+        _ => format!("CIRCUIT_{}", name),
+    }
+}
+
+/// A snake case field name for a pin; pins are often named like keywords (`in`, `out`), so
+/// those get a trailing underscore.
+fn field_name(name: &str) -> String {
+    const KEYWORDS: [&str; 12] = [
+        "as", "fn", "if", "in", "let", "loop", "match", "mod", "ref", "self", "type", "use",
+    ];
+
+    let name = words(name).join("_");
+    let name = match name.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => name,
+        _ => format!("pin_{}", name),
+    };
+
+    if KEYWORDS.contains(&name.as_str()) {
+        name + "_"
+    } else {
+        name
+    }
+}
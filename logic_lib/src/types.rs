@@ -12,24 +12,38 @@ impl std::fmt::Debug for Coordinate {
 
 #[derive(Eq, PartialEq, Clone)]
 pub struct Component {
-    pub lib: u32,
+    /// `None` for instances of another circuit of the project
+    pub lib: Option<u32>,
     pub name: String,
     pub loc: Coordinate,
     pub id: String,
-    pub attributes: std::collections::HashMap<String, String>,
+    pub attributes: std::collections::BTreeMap<String, String>,
     pub component_type: ComponentType,
+    /// Where wires attach, outputs first for gates
+    pub ports: Vec<Port>,
 }
 
 impl std::fmt::Debug for Component {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "\nComponent {{\n  lib: {},\n  name: {},\n  loc: {:?},\n  id: {},\n  attributes: {:?}\n}}",
+            "\nComponent {{\n  lib: {:?},\n  name: {},\n  loc: {:?},\n  id: {},\n  attributes: {:?}\n}}",
             self.lib, self.name, self.loc, self.id, self.attributes
         )
     }
 }
 
+impl Component {
+    /// Where the component faces, east unless its `facing` attribute says otherwise, as in
+    /// Logisim.
+    pub(crate) fn facing(&self) -> &str {
+        self.attributes
+            .get("facing")
+            .map_or("east", |facing| facing.as_str())
+    }
+}
+
+#[derive(Eq, PartialEq, Clone)]
 pub struct Wire {
     pub from: Coordinate,
     pub to: Coordinate,
@@ -41,12 +55,52 @@ impl std::fmt::Debug for Wire {
     }
 }
 
-pub enum ModuleType {
-    Component(Component),
-    WireDestinations(Vec<Coordinate>),
+/// Which way a signal crosses a port, seen from its component: an input pin puts its value out
+/// into the circuit, so its port is an `Output`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum PortDirection {
+    Input,
+    Output,
+    Bidirectional,
 }
 
+/// A point where a component connects to wires.
 #[derive(Eq, PartialEq, Clone)]
+pub struct Port {
+    pub component: String,
+    /// Position among the ports of the component
+    pub index: usize,
+    pub direction: PortDirection,
+    /// How `.logic` code refers to the port, e.g. `in2` or `out`
+    pub name: String,
+    pub loc: Coordinate,
+    /// For subcircuit instances, the id of the pin inside the referenced circuit
+    pub pin: Option<String>,
+}
+
+impl Port {
+    /// Whether the component puts a value out on the net through this port.
+    pub fn drives(&self) -> bool {
+        self.direction != PortDirection::Input
+    }
+
+    /// Whether the component reads the value of the net through this port.
+    pub fn reads(&self) -> bool {
+        self.direction != PortDirection::Output
+    }
+}
+
+impl std::fmt::Debug for Port {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Port {{ {}.{} ({:?}) @ {:?} }}",
+            self.component, self.name, self.direction, self.loc
+        )
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Hash)]
 pub enum ComponentType {
     AND,
     OR,
@@ -56,6 +110,22 @@ pub enum ComponentType {
     XNOR,
     NOT,
     PIN,
+    /// An instance of another circuit of the project, by name
+    Subcircuit(String),
+}
+
+impl ComponentType {
+    /// Distance between the output of a gate and the side its inputs are on, as Logisim draws
+    /// it; `None` for pins and subcircuits.
+    pub(crate) fn depth(&self) -> Option<i64> {
+        match self {
+            ComponentType::AND | ComponentType::OR => Some(30),
+            ComponentType::NAND | ComponentType::NOR | ComponentType::XOR => Some(40),
+            ComponentType::XNOR => Some(50),
+            ComponentType::NOT => Some(20),
+            ComponentType::PIN | ComponentType::Subcircuit(_) => None,
+        }
+    }
 }
 
 impl std::fmt::Debug for ComponentType {
@@ -69,6 +139,15 @@ impl std::fmt::Debug for ComponentType {
             ComponentType::XNOR => write!(f, "XNOR"),
             ComponentType::NOT => write!(f, "NOT"),
             ComponentType::PIN => write!(f, "PIN"),
+            ComponentType::Subcircuit(_) => write!(f, "SUBCIRCUIT"),
         }
     }
 }
+
+/// Whether `name` can be used as an id or port name in `.logic` code as is.
+pub(crate) fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
@@ -0,0 +1,43 @@
+/// Disjoint sets over `0..len`, with path halving and union by size.
+pub(crate) struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(len: usize) -> UnionFind {
+        UnionFind {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+        }
+    }
+
+    /// Adds a new singleton set and returns its element.
+    pub fn push(&mut self) -> usize {
+        self.parents.push(self.parents.len());
+        self.sizes.push(1);
+        self.parents.len() - 1
+    }
+
+    pub fn find(&mut self, mut element: usize) -> usize {
+        while self.parents[element] != element {
+            self.parents[element] = self.parents[self.parents[element]];
+            element = self.parents[element];
+        }
+
+        element
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+    }
+}
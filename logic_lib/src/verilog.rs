@@ -0,0 +1,468 @@
+use crate::circuit::Circuit;
+use crate::netlist::{exactly_one, is_odd_parity, Part, Structure};
+use crate::project::Project;
+use crate::types::{Component, ComponentType};
+
+/// Reserved words of SystemVerilog, which include those of every Verilog version, so that the
+/// modules also read as SystemVerilog.
+const RESERVED_WORDS: [&str; 248] = [
+    "accept_on",
+    "alias",
+    "always",
+    "always_comb",
+    "always_ff",
+    "always_latch",
+    "and",
+    "assert",
+    "assign",
+    "assume",
+    "automatic",
+    "before",
+    "begin",
+    "bind",
+    "bins",
+    "binsof",
+    "bit",
+    "break",
+    "buf",
+    "bufif0",
+    "bufif1",
+    "byte",
+    "case",
+    "casex",
+    "casez",
+    "cell",
+    "chandle",
+    "checker",
+    "class",
+    "clocking",
+    "cmos",
+    "config",
+    "const",
+    "constraint",
+    "context",
+    "continue",
+    "cover",
+    "covergroup",
+    "coverpoint",
+    "cross",
+    "deassign",
+    "default",
+    "defparam",
+    "design",
+    "disable",
+    "dist",
+    "do",
+    "edge",
+    "else",
+    "end",
+    "endcase",
+    "endchecker",
+    "endclass",
+    "endclocking",
+    "endconfig",
+    "endfunction",
+    "endgenerate",
+    "endgroup",
+    "endinterface",
+    "endmodule",
+    "endpackage",
+    "endprimitive",
+    "endprogram",
+    "endproperty",
+    "endsequence",
+    "endspecify",
+    "endtable",
+    "endtask",
+    "enum",
+    "event",
+    "eventually",
+    "expect",
+    "export",
+    "extends",
+    "extern",
+    "final",
+    "first_match",
+    "for",
+    "force",
+    "foreach",
+    "forever",
+    "fork",
+    "forkjoin",
+    "function",
+    "generate",
+    "genvar",
+    "global",
+    "highz0",
+    "highz1",
+    "if",
+    "iff",
+    "ifnone",
+    "ignore_bins",
+    "illegal_bins",
+    "implements",
+    "implies",
+    "import",
+    "incdir",
+    "include",
+    "initial",
+    "inout",
+    "input",
+    "inside",
+    "instance",
+    "int",
+    "integer",
+    "interconnect",
+    "interface",
+    "intersect",
+    "join",
+    "join_any",
+    "join_none",
+    "large",
+    "let",
+    "liblist",
+    "library",
+    "local",
+    "localparam",
+    "logic",
+    "longint",
+    "macromodule",
+    "matches",
+    "medium",
+    "modport",
+    "module",
+    "nand",
+    "negedge",
+    "nettype",
+    "new",
+    "nexttime",
+    "nmos",
+    "nor",
+    "noshowcancelled",
+    "not",
+    "notif0",
+    "notif1",
+    "null",
+    "or",
+    "output",
+    "package",
+    "packed",
+    "parameter",
+    "pmos",
+    "posedge",
+    "primitive",
+    "priority",
+    "program",
+    "property",
+    "protected",
+    "pull0",
+    "pull1",
+    "pulldown",
+    "pullup",
+    "pulsestyle_ondetect",
+    "pulsestyle_onevent",
+    "pure",
+    "rand",
+    "randc",
+    "randcase",
+    "randsequence",
+    "rcmos",
+    "real",
+    "realtime",
+    "ref",
+    "reg",
+    "reject_on",
+    "release",
+    "repeat",
+    "restrict",
+    "return",
+    "rnmos",
+    "rpmos",
+    "rtran",
+    "rtranif0",
+    "rtranif1",
+    "s_always",
+    "s_eventually",
+    "s_nexttime",
+    "s_until",
+    "s_until_with",
+    "scalared",
+    "sequence",
+    "shortint",
+    "shortreal",
+    "showcancelled",
+    "signed",
+    "small",
+    "soft",
+    "solve",
+    "specify",
+    "specparam",
+    "static",
+    "string",
+    "strong",
+    "strong0",
+    "strong1",
+    "struct",
+    "super",
+    "supply0",
+    "supply1",
+    "sync_accept_on",
+    "sync_reject_on",
+    "table",
+    "tagged",
+    "task",
+    "this",
+    "throughout",
+    "time",
+    "timeprecision",
+    "timeunit",
+    "tran",
+    "tranif0",
+    "tranif1",
+    "tri",
+    "tri0",
+    "tri1",
+    "triand",
+    "trior",
+    "trireg",
+    "type",
+    "typedef",
+    "union",
+    "unique",
+    "unique0",
+    "unsigned",
+    "until",
+    "until_with",
+    "untyped",
+    "use",
+    "uwire",
+    "var",
+    "vectored",
+    "virtual",
+    "void",
+    "wait",
+    "wait_order",
+    "wand",
+    "weak",
+    "weak0",
+    "weak1",
+    "while",
+    "wildcard",
+    "wire",
+    "with",
+    "within",
+    "wor",
+    "xnor",
+    "xor",
+];
+
+impl Project {
+    /// Transpiles every circuit into a Verilog module, in the order of their names.
+    pub fn to_verilog(&self) -> String {
+        self.circuits
+            .values()
+            .map(|circuit| circuit.to_verilog())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl Circuit {
+    /// Transpiles the circuit into a structural Verilog module with a port per pin, a wire per
+    /// net, a gate primitive per gate and a module instance per subcircuit.
+    ///
+    /// Ports are named like subcircuit ports, after the pin label or else its id, so instances
+    /// connect to the modules of their circuits by name. Names that are not Verilog identifiers
+    /// are escaped. Unconnected gate inputs are left out, as Logisim does by default.
+    pub fn to_verilog(&self) -> String {
+        let Structure {
+            nets,
+            ports,
+            parts,
+            signals,
+        } = Structure::new(self);
+        let net_name = |net: usize| identifier(&nets.names[net]);
+        let mut negated_wires: Vec<String> = Vec::new();
+        let mut statements: Vec<String> = Vec::new();
+
+        for part in parts {
+            match part {
+                Part::Pin { port, net, output } => {
+                    let (to, from) = if output {
+                        (identifier(&port), net_name(net))
+                    } else {
+                        (net_name(net), identifier(&port))
+                    };
+                    statements.push(format!("assign {} = {};", to, from));
+                }
+                Part::Instance {
+                    component,
+                    circuit,
+                    ports,
+                } => {
+                    let connections: Vec<String> = ports
+                        .into_iter()
+                        .map(|(port, net)| {
+                            let net = net.map(net_name).unwrap_or_default();
+                            format!(".{}({})", identifier(&port), net)
+                        })
+                        .collect();
+
+                    statements.push(format!(
+                        "{} {} ({});",
+                        identifier(circuit),
+                        identifier(&component.id),
+                        connections.join(", ")
+                    ));
+                }
+                Part::Gate {
+                    component,
+                    gate,
+                    output,
+                    inputs,
+                } => {
+                    let mut operands: Vec<String> = Vec::new();
+                    for input in inputs {
+                        if input.negated {
+                            let negated =
+                                identifier(&format!("{}_negate{}", component.id, input.index));
+                            statements.push(format!("not ({}, {});", negated, net_name(input.net)));
+                            negated_wires.push(negated.clone());
+                            operands.push(negated);
+                        } else {
+                            operands.push(net_name(input.net));
+                        }
+                    }
+
+                    statements.push(gate_statement(component, gate, net_name(output), operands));
+                }
+            }
+        }
+
+        let mut verilog = String::new();
+        verilog.push_str(format!("module {} (\n", identifier(&self.name)).as_str());
+        verilog.push_str(
+            ports
+                .iter()
+                .map(|(port, output)| {
+                    let direction = if *output { "output" } else { "input" };
+                    format!("    {} {}", direction, identifier(port))
+                })
+                .collect::<Vec<String>>()
+                .join(",\n")
+                .as_str(),
+        );
+        verilog.push_str("\n);\n");
+
+        let wires: Vec<String> = signals
+            .into_iter()
+            .map(net_name)
+            .chain(negated_wires)
+            .collect();
+        for wire in &wires {
+            verilog.push_str(format!("    wire {};\n", wire).as_str());
+        }
+        if !wires.is_empty() {
+            verilog.push('\n');
+        }
+
+        for unresolved in &nets.unresolved {
+            verilog.push_str(
+                format!("    // {}: no port given, left unconnected\n", unresolved).as_str(),
+            );
+        }
+        for statement in &statements {
+            verilog.push_str(format!("    {}\n", statement).as_str());
+        }
+        verilog.push_str("endmodule\n");
+
+        verilog
+    }
+}
+
+fn gate_statement(
+    component: &Component,
+    gate: &ComponentType,
+    output: String,
+    inputs: Vec<String>,
+) -> String {
+    let instance = identifier(&component.id);
+
+    if inputs.is_empty() {
+        return format!("assign {} = 1'bx;", output);
+    }
+
+    // Verilog's xor is parity
+    let parity = is_odd_parity(component) || inputs.len() <= 2;
+    if matches!(gate, ComponentType::XOR | ComponentType::XNOR) && !parity {
+        let terms: Vec<String> = exactly_one(inputs.len())
+            .into_iter()
+            .map(|term| {
+                let literals: Vec<String> = term
+                    .into_iter()
+                    .map(|(input, complemented)| {
+                        if complemented {
+                            format!("~{}", inputs[input])
+                        } else {
+                            inputs[input].clone()
+                        }
+                    })
+                    .collect();
+                format!("({})", literals.join(" & "))
+            })
+            .collect();
+
+        return if *gate == ComponentType::XNOR {
+            format!("assign {} = ~({});", output, terms.join(" | "))
+        } else {
+            format!("assign {} = {};", output, terms.join(" | "))
+        };
+    }
+
+    let primitive = match gate {
+        ComponentType::AND => "and",
+        ComponentType::OR => "or",
+        ComponentType::NAND => "nand",
+        ComponentType::NOR => "nor",
+        ComponentType::XOR => "xor",
+        ComponentType::XNOR => "xnor",
+        ComponentType::NOT => "not",
+        _ => unreachable!("pins and subcircuits are not gates"),
+    };
+
+    format!(
+        "{} {} ({}, {});",
+        primitive,
+        instance,
+        output,
+        inputs.join(", ")
+    )
+}
+
+/// `name` if it is a Verilog identifier, or else escaped, e.g. `half adder` as
+/// `\half\u{20}adder `. Characters escaped identifiers cannot hold, and the backslash that
+/// marks them, are written as their code point, so different names stay different.
+fn identifier(name: &str) -> String {
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+
+    if plain && !RESERVED_WORDS.contains(&name) {
+        String::from(name)
+    } else {
+        let mut escaped = String::from("\\");
+        for c in name.chars() {
+            if c.is_ascii_graphic() && c != '\\' {
+                escaped.push(c);
+            } else {
+                escaped.push_str(&format!("\\u{{{:x}}}", c as u32));
+            }
+        }
+        escaped.push(' ');
+        escaped
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::BTreeSet;
+
+use crate::circuit::Circuit;
+use crate::netlist::{exactly_one, is_odd_parity, Part, Structure};
+use crate::project::Project;
+use crate::types::{Component, ComponentType};
+
+/// Reserved words of VHDL-2008, PSL's included. VHDL ignores case, so names are compared to them
+/// in lower case.
+const RESERVED_WORDS: [&str; 115] = [
+    "abs",
+    "access",
+    "after",
+    "alias",
+    "all",
+    "and",
+    "architecture",
+    "array",
+    "assert",
+    "assume",
+    "assume_guarantee",
+    "attribute",
+    "begin",
+    "block",
+    "body",
+    "buffer",
+    "bus",
+    "case",
+    "component",
+    "configuration",
+    "constant",
+    "context",
+    "cover",
+    "default",
+    "disconnect",
+    "downto",
+    "else",
+    "elsif",
+    "end",
+    "entity",
+    "exit",
+    "fairness",
+    "file",
+    "for",
+    "force",
+    "function",
+    "generate",
+    "generic",
+    "group",
+    "guarded",
+    "if",
+    "impure",
+    "in",
+    "inertial",
+    "inout",
+    "is",
+    "label",
+    "library",
+    "linkage",
+    "literal",
+    "loop",
+    "map",
+    "mod",
+    "nand",
+    "new",
+    "next",
+    "nor",
+    "not",
+    "null",
+    "of",
+    "on",
+    "open",
+    "or",
+    "others",
+    "out",
+    "package",
+    "parameter",
+    "port",
+    "postponed",
+    "procedure",
+    "process",
+    "property",
+    "protected",
+    "pure",
+    "range",
+    "record",
+    "register",
+    "reject",
+    "release",
+    "rem",
+    "report",
+    "restrict",
+    "restrict_guarantee",
+    "return",
+    "rol",
+    "ror",
+    "select",
+    "sequence",
+    "severity",
+    "shared",
+    "signal",
+    "sla",
+    "sll",
+    "sra",
+    "srl",
+    "strong",
+    "subtype",
+    "then",
+    "to",
+    "transport",
+    "type",
+    "unaffected",
+    "units",
+    "until",
+    "use",
+    "variable",
+    "vmode",
+    "vprop",
+    "vunit",
+    "wait",
+    "when",
+    "while",
+    "with",
+    "xnor",
+    "xor",
+];
+
+impl Project {
+    /// Transpiles every circuit into a VHDL entity and architecture, each circuit after the
+    /// circuits it instantiates so the file can be analyzed in one go.
+    pub fn to_vhdl(&self) -> String {
+        let mut order: Vec<&Circuit> = Vec::new();
+        let mut visited: BTreeSet<&str> = BTreeSet::new();
+
+        for name in self.circuits.keys() {
+            self.visit(name, &mut visited, &mut order);
+        }
+
+        order
+            .iter()
+            .map(|circuit| circuit.to_vhdl())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn visit<'a>(
+        &'a self,
+        name: &'a str,
+        visited: &mut BTreeSet<&'a str>,
+        order: &mut Vec<&'a Circuit>,
+    ) {
+        let Some(circuit) = self.circuit(name) else {
+            return;
+        };
+        if !visited.insert(name) {
+            return;
+        }
+
+        for component in &circuit.components {
+            if let ComponentType::Subcircuit(subcircuit) = &component.component_type {
+                self.visit(subcircuit, visited, order);
+            }
+        }
+        order.push(circuit);
+    }
+}
+
+impl Circuit {
+    /// Transpiles the circuit into a VHDL-2008 entity with a `std_logic` port per pin and a
+    /// structural architecture with a signal per net, a concurrent assignment per gate and an
+    /// entity instance per subcircuit.
+    ///
+    /// `std_logic` is resolved, so input pins wired together all drive their net, which is
+    /// `'X'` where they disagree, like the error value Logisim shows. Gates read output ports directly, which VHDL-2008
+    /// allows. Names that are not basic identifiers, or that match a reserved word in any case,
+    /// become extended identifiers, which keep their case. Open gate inputs are simply left
+    /// out of the expressions, and open instance ports are mapped to `open`.
+    pub fn to_vhdl(&self) -> String {
+        let Structure {
+            nets,
+            ports,
+            parts,
+            signals,
+        } = Structure::new(self);
+        let net_name = |net: usize| identifier(&nets.names[net]);
+        let mut statements: Vec<String> = Vec::new();
+
+        for part in parts {
+            match part {
+                Part::Pin { port, net, output } => {
+                    let (to, from) = if output {
+                        (identifier(&port), net_name(net))
+                    } else {
+                        (net_name(net), identifier(&port))
+                    };
+                    statements.push(format!("{} <= {};", to, from));
+                }
+                Part::Instance {
+                    component,
+                    circuit,
+                    ports,
+                } => {
+                    let associations: Vec<String> = ports
+                        .into_iter()
+                        .map(|(port, net)| {
+                            format!(
+                                "{} => {}",
+                                identifier(&port),
+                                net.map(net_name).unwrap_or_else(|| String::from("open"))
+                            )
+                        })
+                        .collect();
+
+                    statements.push(format!(
+                        "{} : entity work.{} port map ({});",
+                        identifier(&component.id),
+                        identifier(circuit),
+                        associations.join(", ")
+                    ));
+                }
+                Part::Gate {
+                    component,
+                    gate,
+                    output,
+                    inputs,
+                } => {
+                    let operands: Vec<String> = inputs
+                        .into_iter()
+                        .map(|input| {
+                            if input.negated {
+                                invert(&net_name(input.net))
+                            } else {
+                                net_name(input.net)
+                            }
+                        })
+                        .collect();
+
+                    statements.push(format!(
+                        "{} : {} <= {};",
+                        identifier(&component.id),
+                        net_name(output),
+                        gate_expression(component, gate, operands)
+                    ));
+                }
+            }
+        }
+
+        let name = identifier(&self.name);
+        let mut vhdl = String::new();
+        vhdl.push_str("library ieee;\nuse ieee.std_logic_1164.all;\n\n");
+        vhdl.push_str(format!("entity {} is\n", name).as_str());
+        if !ports.is_empty() {
+            vhdl.push_str("    port (\n");
+            vhdl.push_str(
+                ports
+                    .iter()
+                    .map(|(port, output)| {
+                        let mode = if *output { "out" } else { "in" };
+                        format!("        {} : {} std_logic", identifier(port), mode)
+                    })
+                    .collect::<Vec<String>>()
+                    .join(";\n")
+                    .as_str(),
+            );
+            vhdl.push_str("\n    );\n");
+        }
+        vhdl.push_str(format!("end entity {};\n\n", name).as_str());
+
+        vhdl.push_str(format!("architecture structural of {} is\n", name).as_str());
+        for net in signals {
+            vhdl.push_str(format!("    signal {} : std_logic;\n", net_name(net)).as_str());
+        }
+        vhdl.push_str("begin\n");
+        for unresolved in &nets.unresolved {
+            vhdl.push_str(
+                format!("    -- {}: no port given, left unconnected\n", unresolved).as_str(),
+            );
+        }
+        for statement in &statements {
+            vhdl.push_str(format!("    {}\n", statement).as_str());
+        }
+        vhdl.push_str("end architecture structural;\n");
+
+        vhdl
+    }
+}
+
+fn gate_expression(component: &Component, gate: &ComponentType, inputs: Vec<String>) -> String {
+    if inputs.is_empty() {
+        return String::from("'X'");
+    }
+
+    let join = |operator: &str| {
+        if inputs.len() == 1 {
+            inputs[0].clone()
+        } else {
+            inputs.join(format!(" {} ", operator).as_str())
+        }
+    };
+    let one_high = || {
+        let terms: Vec<String> = exactly_one(inputs.len())
+            .into_iter()
+            .map(|term| {
+                let literals: Vec<String> = term
+                    .into_iter()
+                    .map(|(input, complemented)| {
+                        if complemented {
+                            invert(&inputs[input])
+                        } else {
+                            inputs[input].clone()
+                        }
+                    })
+                    .collect();
+                format!("({})", literals.join(" and "))
+            })
+            .collect();
+        terms.join(" or ")
+    };
+    // `xor` on several operands chains, so gives parity like an odd-parity gate
+    let parity = is_odd_parity(component) || inputs.len() <= 2;
+
+    match gate {
+        ComponentType::NOT => invert(&inputs[0]),
+        ComponentType::AND => join("and"),
+        ComponentType::NAND => invert(&join("and")),
+        ComponentType::OR => join("or"),
+        ComponentType::NOR => invert(&join("or")),
+        ComponentType::XOR if parity => join("xor"),
+        ComponentType::XOR => one_high(),
+        ComponentType::XNOR if parity => invert(&join("xor")),
+        ComponentType::XNOR => invert(&one_high()),
+        _ => unreachable!("pins and subcircuits are not gates"),
+    }
+}
+
+/// `not` applied to an operand, which needs parentheses unless it is a single name.
+fn invert(operand: &str) -> String {
+    if is_name(operand) {
+        format!("not {}", operand)
+    } else {
+        format!("not ({})", operand)
+    }
+}
+
+/// Whether `operand` is a single basic or extended identifier rather than an expression.
+fn is_name(operand: &str) -> bool {
+    let Some(extended) = operand.strip_prefix('\\') else {
+        return !operand.contains(' ');
+    };
+
+    // Inside an extended identifier a backslash is doubled, a single one ends it
+    let mut chars = extended.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.next() != Some('\\') {
+            return chars.next().is_none();
+        }
+    }
+    false
+}
+
+/// `name` if it is a basic VHDL identifier, or else an extended identifier like `\half adder\`,
+/// with backslashes doubled. Basic identifiers ignore case and extended ones do not, so `Out`
+/// is written `\Out\` to keep it from meaning the reserved word `out`.
+fn identifier(name: &str) -> String {
+    let mut chars = name.chars();
+    let basic = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.contains("__")
+        && !name.ends_with('_');
+
+    if basic && !RESERVED_WORDS.contains(&name.to_ascii_lowercase().as_str()) {
+        String::from(name)
+    } else {
+        format!("\\{}\\", name.replace('\\', "\\\\"))
+    }
+}
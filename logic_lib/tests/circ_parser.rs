@@ -0,0 +1,477 @@
+use std::time::{Duration, Instant};
+
+use logic_lib::{
+    circ_parser::CircParser,
+    diagnostics::Severity,
+    error::ParseError,
+    types::{Coordinate, PortDirection},
+};
+
+/// A `.circ` file with a single `main` circuit made of the given `<comp>` and `<wire>` elements.
+fn circ(body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+         <project source=\"2.16.1.4.jar\" version=\"1.0\">\n\
+         <lib desc=\"#Wiring\" name=\"0\"/>\n\
+         <lib desc=\"#Gates\" name=\"1\"/>\n\
+         <main name=\"main\"/>\n\
+         <circuit name=\"main\">\n{}</circuit>\n\
+         </project>\n",
+        body
+    )
+}
+
+/// An input pin, a chain of `gates` NOT gates 60 units apart and an output pin.
+fn not_chain(gates: u32) -> String {
+    let mut body = String::from(
+        "<comp lib=\"0\" loc=\"(20,20)\" name=\"Pin\"/>\n<wire from=\"(20,20)\" to=\"(40,20)\"/>\n",
+    );
+    for gate in 0..gates {
+        let x = 60 + 60 * gate;
+        body.push_str(&format!(
+            "<comp lib=\"1\" loc=\"({},20)\" name=\"NOT Gate\"/>\n\
+             <wire from=\"({},20)\" to=\"({},20)\"/>\n",
+            x,
+            x,
+            x + 40
+        ));
+    }
+    body.push_str(&format!(
+        "<comp lib=\"0\" loc=\"({},20)\" name=\"Pin\">\n<a name=\"output\" val=\"true\"/>\n</comp>\n",
+        60 * gates + 40
+    ));
+
+    circ(&body)
+}
+
+#[test]
+fn parsing_time_grows_linearly_with_the_circuit() {
+    // The fastest of a few runs, to keep scheduling noise out of the comparison
+    let parse_time = |text: &str| -> Duration {
+        (0..3)
+            .map(|_| {
+                let start = Instant::now();
+                text.parse::<CircParser>().unwrap().parse().unwrap();
+                start.elapsed()
+            })
+            .min()
+            .unwrap()
+    };
+    let small = parse_time(&not_chain(2_000));
+    let large = parse_time(&not_chain(16_000));
+
+    // Eight times the gates take about eight times as long to parse, quadratic parsing 64 times
+    assert!(
+        large < small * 24,
+        "2000 gates took {:?}, 16000 gates {:?}",
+        small,
+        large
+    );
+
+    let circuit = not_chain(100)
+        .parse::<CircParser>()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(circuit.components.len(), 102);
+    assert_eq!(circuit.connections.len(), 101);
+    assert_eq!(circuit.connections[0].source(), "comp_0.out");
+    assert_eq!(circuit.connections[0].target(), "comp_1.in");
+}
+
+#[test]
+fn wire_ends_on_a_segment_join_it_but_crossings_do_not() {
+    // `a` runs along y=40 into comp_2, and a branch off its middle at (60,40) leads down into
+    // comp_1; `b` runs along y=60 into comp_4, crossing the branch at (60,60)
+    let text = circ(
+        "<wire from=\"(20,40)\" to=\"(120,40)\"/>\n\
+         <wire from=\"(60,40)\" to=\"(60,80)\"/>\n\
+         <wire from=\"(60,80)\" to=\"(80,80)\"/>\n\
+         <wire from=\"(20,60)\" to=\"(100,60)\"/>\n\
+         <comp lib=\"0\" loc=\"(20,40)\" name=\"Pin\">\n<a name=\"label\" val=\"a\"/>\n</comp>\n\
+         <comp lib=\"1\" loc=\"(100,80)\" name=\"NOT Gate\"/>\n\
+         <comp lib=\"1\" loc=\"(140,40)\" name=\"NOT Gate\"/>\n\
+         <comp lib=\"0\" loc=\"(20,60)\" name=\"Pin\">\n<a name=\"label\" val=\"b\"/>\n</comp>\n\
+         <comp lib=\"1\" loc=\"(120,60)\" name=\"NOT Gate\"/>\n",
+    );
+    let circuit = text.parse::<CircParser>().unwrap().parse().unwrap();
+
+    let components_on = |x: u32, y: u32| {
+        let mut components: Vec<String> = circuit
+            .net_at(Coordinate { x, y })
+            .unwrap()
+            .ports
+            .iter()
+            .map(|port| port.component.clone())
+            .collect();
+        components.sort();
+        components
+    };
+    assert_eq!(components_on(20, 40), ["comp_0", "comp_1", "comp_2"]);
+    assert_eq!(components_on(60, 80), ["comp_0", "comp_1", "comp_2"]);
+    assert_eq!(components_on(20, 60), ["comp_3", "comp_4"]);
+
+    let targets = |from: &str| -> Vec<String> {
+        circuit
+            .connections_from(from)
+            .map(|connection| connection.target())
+            .collect()
+    };
+    assert_eq!(targets("comp_0"), ["comp_1.in", "comp_2.in"]);
+    assert_eq!(targets("comp_3"), ["comp_4.in"]);
+}
+
+#[test]
+fn returns_warnings_with_the_circuit() {
+    let text = circ(
+        "<wire from=\"(20,20)\" to=\"(60,20)\"/>\n\
+         <comp lib=\"0\" loc=\"(20,20)\" name=\"Pin\"/>\n\
+         <comp lib=\"1\" loc=\"(200,200)\" name=\"NOT Gate\"/>\n",
+    );
+    let parsed = text
+        .parse::<CircParser>()
+        .unwrap()
+        .parse_with_diagnostics()
+        .unwrap();
+
+    assert_eq!(parsed.value.components.len(), 2);
+    let messages: Vec<String> = parsed
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "<input>: warning: main/comp_1: the component does not have any wires connected to it at (200,200)",
+            "<input>: warning: main: the wire end does not touch anything at (60,20)",
+        ]
+    );
+    assert!(parsed
+        .diagnostics
+        .warnings()
+        .all(|diagnostic| diagnostic.severity == Severity::Warning));
+    assert!(!parsed.diagnostics.has_errors());
+}
+
+#[test]
+fn errors_name_the_file_element_and_attribute() {
+    let missing = circ("<comp lib=\"1\" name=\"NOT Gate\"/>\n")
+        .parse::<CircParser>()
+        .unwrap()
+        .with_file_name("gates.circ")
+        .parse()
+        .unwrap_err();
+    assert!(matches!(
+        &missing,
+        ParseError::MissingAttribute { file: Some(file), element, attribute }
+            if file.to_str() == Some("gates.circ") && element == "comp" && attribute == "loc"
+    ));
+    assert_eq!(
+        missing.to_string(),
+        "gates.circ: <comp> is missing the `loc` attribute"
+    );
+
+    let coordinate = circ("<wire from=\"(20;20)\" to=\"(40,20)\"/>\n")
+        .parse::<CircParser>()
+        .unwrap()
+        .parse()
+        .unwrap_err();
+    assert!(matches!(
+        &coordinate,
+        ParseError::InvalidCoordinate { file: None, element, attribute, value }
+            if element == "wire" && attribute == "from" && value == "(20;20)"
+    ));
+    assert_eq!(
+        coordinate.to_string(),
+        "<input>: <wire> has an invalid coordinate in `from`: \"(20;20)\""
+    );
+
+    let lib = circ("<comp lib=\"gates\" loc=\"(20,20)\" name=\"NOT Gate\"/>\n")
+        .parse::<CircParser>()
+        .unwrap()
+        .parse()
+        .unwrap_err();
+    assert!(matches!(
+        &lib,
+        ParseError::InvalidAttribute { element, attribute, value, .. }
+            if element == "comp" && attribute == "lib" && value == "gates"
+    ));
+
+    let unsupported = circ("<comp lib=\"1\" loc=\"(20,20)\" name=\"Buffer\"/>\n")
+        .parse::<CircParser>()
+        .unwrap()
+        .parse()
+        .unwrap_err();
+    assert!(matches!(
+        &unsupported,
+        ParseError::UnsupportedComponent { name, .. } if name == "Buffer"
+    ));
+
+    let xml = "<project>"
+        .parse::<CircParser>()
+        .unwrap()
+        .parse()
+        .unwrap_err();
+    assert!(matches!(xml, ParseError::Xml { file: None, .. }));
+
+    let io = CircParser::new("tests/missing.circ").parse().unwrap_err();
+    assert!(matches!(
+        &io,
+        ParseError::Io { file: Some(file), .. } if file.ends_with("missing.circ")
+    ));
+    assert!(io
+        .to_string()
+        .starts_with("tests/missing.circ: failed to read file: "));
+}
+
+#[test]
+fn parses_files_text_bytes_and_readers_alike() {
+    let circuit = CircParser::new("tests/test.circ").parse().unwrap();
+    let text = std::fs::read_to_string("tests/test.circ").unwrap();
+
+    assert_eq!(
+        text.parse::<CircParser>().unwrap().parse().unwrap(),
+        circuit
+    );
+    assert_eq!(
+        CircParser::from_bytes(text.as_bytes()).parse().unwrap(),
+        circuit
+    );
+    assert_eq!(
+        CircParser::from_reader(text.as_bytes())
+            .unwrap()
+            .parse()
+            .unwrap(),
+        circuit
+    );
+
+    // In-memory sources have no file name unless they are given one
+    let error = CircParser::from_bytes("<project/>").parse().unwrap_err();
+    assert!(error.file().is_none());
+    let error = CircParser::from_bytes("<project/>")
+        .with_file_name("stdin.circ")
+        .parse()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "stdin.circ: missing <circuit> element");
+}
+
+#[test]
+fn reader_errors_are_io_errors() {
+    struct Broken;
+
+    impl std::io::Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("broken pipe"))
+        }
+    }
+
+    assert!(matches!(
+        CircParser::from_reader(Broken),
+        Err(ParseError::Io { file: None, .. })
+    ));
+}
+
+/// A project with an empty `first` circuit and a `second` one holding a single pin, with the
+/// given `<main>` element.
+fn two_circuits(main: &str) -> CircParser {
+    format!(
+        "<project source=\"2.16.1.4.jar\" version=\"1.0\">\n\
+         <lib desc=\"#Wiring\" name=\"0\"/>\n\
+         {}\n\
+         <circuit name=\"first\"/>\n\
+         <circuit name=\"second\">\n\
+         <comp lib=\"0\" loc=\"(20,20)\" name=\"Pin\"/>\n\
+         </circuit>\n\
+         </project>\n",
+        main
+    )
+    .parse()
+    .unwrap()
+}
+
+#[test]
+fn parses_every_circuit_and_picks_the_main_one() {
+    let mut parser = two_circuits("<main name=\"second\"/>");
+    let project = parser.parse_project().unwrap();
+    assert_eq!(
+        project.circuits.keys().collect::<Vec<_>>(),
+        ["first", "second"]
+    );
+    assert_eq!(project.main, "second");
+    assert_eq!(project.main_circuit().unwrap().components.len(), 1);
+    assert_eq!(parser.parse().unwrap().name, "second");
+
+    // Without a <main> element the first circuit is the main one
+    assert_eq!(two_circuits("").parse().unwrap().name, "first");
+
+    // A <main> naming no circuit falls back to the first one, with a warning
+    let parsed = two_circuits("<main name=\"third\"/>")
+        .parse_with_diagnostics()
+        .unwrap();
+    assert_eq!(parsed.value.name, "first");
+    assert!(parsed
+        .diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.to_string()
+            == "<input>: warning: main circuit \"third\" does not exist, using \"first\" instead"));
+}
+
+#[test]
+fn transpiles_circuits_by_name() {
+    let mut parser = two_circuits("<main name=\"first\"/>");
+    assert!(parser
+        .transpile_circuit_to_logic_code("second")
+        .unwrap()
+        .contains("comp_0"));
+    assert_eq!(
+        parser
+            .transpile_all_to_logic_code()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        ["first", "second"]
+    );
+    assert!(matches!(
+        parser.transpile_circuit_to_logic_code("third"),
+        Err(ParseError::UnknownCircuit { name, .. }) if name == "third"
+    ));
+}
+
+/// A project whose `main` circuit holds one instance of `half`, drawn with the given `<appear>`
+/// element.
+fn custom_appearance(appear: &str) -> CircParser {
+    format!(
+        "<project source=\"2.16.1.4.jar\" version=\"1.0\">\n\
+         <lib desc=\"#Wiring\" name=\"0\"/>\n\
+         <main name=\"main\"/>\n\
+         <circuit name=\"half\">\n\
+         {}\n\
+         <comp lib=\"0\" loc=\"(20,20)\" name=\"Pin\">\n<a name=\"label\" val=\"a\"/>\n</comp>\n\
+         <comp lib=\"0\" loc=\"(100,20)\" name=\"Pin\">\n\
+         <a name=\"output\" val=\"true\"/>\n<a name=\"label\" val=\"y\"/>\n</comp>\n\
+         </circuit>\n\
+         <circuit name=\"main\">\n\
+         <comp loc=\"(200,100)\" name=\"half\"/>\n\
+         </circuit>\n\
+         </project>\n",
+        appear
+    )
+    .parse()
+    .unwrap()
+}
+
+#[test]
+fn reads_custom_appearances() {
+    // The anchor is centered on (60,50), `a` 20 units west of it and `y` on it
+    let mut parser = custom_appearance(
+        "<appear>\n\
+         <rect height=\"40\" width=\"40\" x=\"30\" y=\"30\"/>\n\
+         <circ-port height=\"10\" pin=\"(20,20)\" width=\"10\" x=\"35\" y=\"45\"/>\n\
+         <circ-port height=\"10\" pin=\"(100,20)\" width=\"10\" x=\"55\" y=\"45\"/>\n\
+         <circ-port height=\"10\" pin=\"(300,300)\" width=\"10\" x=\"35\" y=\"35\"/>\n\
+         <circ-anchor facing=\"east\" height=\"6\" width=\"6\" x=\"57\" y=\"47\"/>\n\
+         </appear>",
+    );
+    let parsed = parser.parse_with_diagnostics().unwrap();
+
+    let ports: Vec<(&str, PortDirection, Coordinate, Option<&str>)> = parsed.value.components[0]
+        .ports
+        .iter()
+        .map(|port| {
+            (
+                port.name.as_str(),
+                port.direction,
+                port.loc,
+                port.pin.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        ports,
+        [
+            (
+                "a",
+                PortDirection::Input,
+                Coordinate { x: 180, y: 100 },
+                Some("comp_0")
+            ),
+            (
+                "y",
+                PortDirection::Output,
+                Coordinate { x: 200, y: 100 },
+                Some("comp_1")
+            ),
+        ]
+    );
+
+    // The port without a pin behind it is left out
+    assert!(parsed.diagnostics.iter().any(|diagnostic| {
+        diagnostic.to_string()
+        == "<input>: warning: half: the appearance refers to a pin that does not exist at (300,300)"
+    }));
+}
+
+#[test]
+fn custom_appearances_need_an_anchor() {
+    let error = custom_appearance(
+        "<appear>\n\
+         <circ-port height=\"10\" pin=\"(20,20)\" width=\"10\" x=\"35\" y=\"45\"/>\n\
+         </appear>",
+    )
+    .parse()
+    .unwrap_err();
+
+    assert!(matches!(
+        error,
+        ParseError::MissingElement { element, .. } if element == "circ-anchor"
+    ));
+}
+
+#[test]
+fn ports_have_directions_and_shorted_outputs_are_errors() {
+    // Two input pins drive the same wire into a NOT gate, which drives an output pin
+    let text = circ(
+        "<wire from=\"(20,20)\" to=\"(80,20)\"/>\n\
+         <wire from=\"(100,20)\" to=\"(120,20)\"/>\n\
+         <comp lib=\"0\" loc=\"(20,20)\" name=\"Pin\"/>\n\
+         <comp lib=\"0\" loc=\"(40,20)\" name=\"Pin\"/>\n\
+         <comp lib=\"1\" loc=\"(100,20)\" name=\"NOT Gate\"/>\n\
+         <comp lib=\"0\" loc=\"(120,20)\" name=\"Pin\">\n<a name=\"output\" val=\"true\"/>\n</comp>\n",
+    );
+    let parsed = text
+        .parse::<CircParser>()
+        .unwrap()
+        .parse_with_diagnostics()
+        .unwrap();
+
+    let ports: Vec<(String, PortDirection)> = parsed
+        .value
+        .components
+        .iter()
+        .flat_map(|component| &component.ports)
+        .map(|port| (format!("{}.{}", port.component, port.name), port.direction))
+        .collect();
+    assert_eq!(
+        ports,
+        [
+            (String::from("comp_0.out"), PortDirection::Output),
+            (String::from("comp_1.out"), PortDirection::Output),
+            (String::from("comp_2.out"), PortDirection::Output),
+            (String::from("comp_2.in"), PortDirection::Input),
+            (String::from("comp_3.in"), PortDirection::Input),
+        ]
+    );
+
+    assert!(parsed.diagnostics.has_errors());
+    let errors: Vec<String> = parsed
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .map(|diagnostic| diagnostic.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        ["<input>: error: main: the outputs of comp_0, comp_1 are wired together at (20,20)"]
+    );
+}
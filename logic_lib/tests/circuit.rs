@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use logic_lib::{
+    circuit::{Circuit, Connection, Net},
+    types::{Component, ComponentType, Coordinate, Port, PortDirection},
+};
+
+fn component(id: &str, component_type: ComponentType) -> Component {
+    Component {
+        lib: Some(1),
+        name: String::new(),
+        loc: Coordinate { x: 0, y: 0 },
+        id: String::from(id),
+        attributes: BTreeMap::new(),
+        component_type,
+        ports: Vec::new(),
+    }
+}
+
+fn port(component: &str, index: usize, direction: PortDirection, name: &str) -> Port {
+    Port {
+        component: String::from(component),
+        index,
+        direction,
+        name: String::from(name),
+        loc: Coordinate { x: 0, y: 0 },
+        pin: None,
+    }
+}
+
+fn net(id: &str, x: u32, ports: Vec<Port>) -> Net {
+    Net {
+        id: String::from(id),
+        points: vec![Coordinate { x, y: 0 }],
+        ports,
+    }
+}
+
+/// `a` drives the first input of `g`, `b` its second, and `g` drives `out`.
+fn and_circuit() -> Circuit {
+    let mut circuit = Circuit::new("main");
+    circuit.add_component(component("a", ComponentType::PIN));
+    circuit.add_component(component("b", ComponentType::PIN));
+    circuit.add_component(component("g", ComponentType::AND));
+    circuit.add_component(component("out", ComponentType::PIN));
+    circuit.nets = vec![
+        net(
+            "net_0",
+            10,
+            vec![
+                port("a", 0, PortDirection::Output, "out"),
+                port("g", 1, PortDirection::Input, "in1"),
+            ],
+        ),
+        net(
+            "net_1",
+            20,
+            vec![
+                port("g", 2, PortDirection::Input, "in2"),
+                port("b", 0, PortDirection::Output, "out"),
+            ],
+        ),
+        net(
+            "net_2",
+            30,
+            vec![
+                port("g", 0, PortDirection::Output, "out"),
+                port("out", 0, PortDirection::Input, "in"),
+            ],
+        ),
+    ];
+    circuit.derive_connections();
+
+    circuit
+}
+
+#[test]
+fn connections_run_from_drivers_to_readers_in_component_order() {
+    let circuit = and_circuit();
+
+    let connections: Vec<(String, String)> = circuit
+        .connections
+        .iter()
+        .map(|connection| (connection.source(), connection.target()))
+        .collect();
+    assert_eq!(
+        connections,
+        [
+            (String::from("a.out"), String::from("g.in1")),
+            (String::from("b.out"), String::from("g.in2")),
+            (String::from("g.out"), String::from("out.in")),
+        ]
+    );
+
+    assert_eq!(circuit.connections_to("g").count(), 2);
+    assert_eq!(circuit.connections_from("g").count(), 1);
+    assert_eq!(
+        circuit.net_at(Coordinate { x: 20, y: 0 }).unwrap().id,
+        "net_1"
+    );
+    assert!(circuit.net_at(Coordinate { x: 40, y: 0 }).is_none());
+    let nets: Vec<&str> = circuit.nets_of("g").map(|net| net.id.as_str()).collect();
+    assert_eq!(nets, ["net_0", "net_1", "net_2"]);
+}
+
+#[test]
+fn removing_a_component_drops_its_ports_and_connections() {
+    let mut circuit = and_circuit();
+
+    let removed = circuit.remove_component("b").unwrap();
+    assert_eq!(removed.id, "b");
+    assert!(circuit.component("b").is_none());
+    assert!(circuit.remove_component("b").is_none());
+    assert_eq!(circuit.nets[1].ports.len(), 1);
+    assert_eq!(circuit.connections_to("g").count(), 1);
+
+    circuit.component_mut("g").unwrap().component_type = ComponentType::OR;
+    assert_eq!(
+        circuit.component("g").unwrap().component_type,
+        ComponentType::OR
+    );
+}
+
+#[test]
+fn connections_are_only_added_once() {
+    let mut circuit = Circuit::new("main");
+    circuit.connect("a", "g");
+    circuit.connect("a", "g");
+    circuit.connect_ports("a", "out", "g", "in1");
+    circuit.add_connection(Connection {
+        from: String::from("a"),
+        from_port: Some(String::from("out")),
+        to: String::from("g"),
+        to_port: Some(String::from("in1")),
+    });
+
+    let ends: Vec<(String, String)> = circuit
+        .connections
+        .iter()
+        .map(|connection| (connection.source(), connection.target()))
+        .collect();
+    assert_eq!(
+        ends,
+        [
+            (String::from("a"), String::from("g")),
+            (String::from("a.out"), String::from("g.in1")),
+        ]
+    );
+}
+
+#[test]
+fn lookups_follow_changes_made_to_the_vectors() {
+    let mut circuit = and_circuit();
+
+    circuit.components.swap(0, 3);
+    circuit.components.push(component("c", ComponentType::PIN));
+    assert_eq!(circuit.component("a").unwrap().id, "a");
+    assert_eq!(circuit.component("c").unwrap().id, "c");
+
+    circuit.connections.clear();
+    circuit.connect_ports("a", "out", "g", "in1");
+    circuit.connections.push(circuit.connections[0].clone());
+    circuit.connect_ports("g", "out", "out", "in");
+    circuit.connect_ports("g", "out", "out", "in");
+    assert_eq!(circuit.connections.len(), 3);
+}
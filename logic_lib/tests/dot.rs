@@ -0,0 +1,47 @@
+use logic_lib::{circ_parser::CircParser, logic_parser::LogicParser};
+
+#[test]
+fn latch_graph() {
+    let dot = CircParser::new("tests/test.circ")
+        .transpile_to_dot()
+        .unwrap();
+
+    assert_eq!(
+        dot,
+        "digraph \"main\" {\n    rankdir=LR;\n\n\
+         \x20   \"comp_0\" [label=\"NOR\", shape=box, style=rounded];\n\
+         \x20   \"comp_1\" [label=\"comp_1\", shape=square];\n\
+         \x20   \"comp_2\" [label=\"comp_2\", shape=square];\n\
+         \x20   \"comp_3\" [label=\"comp_3\", shape=circle];\n\
+         \x20   \"comp_4\" [label=\"NOR\", shape=box, style=rounded];\n\
+         \x20   { rank=source; \"comp_1\"; \"comp_2\"; }\n\
+         \x20   { rank=sink; \"comp_3\"; }\n\n\
+         \x20   \"comp_0\" -> \"comp_3\";\n\
+         \x20   \"comp_0\" -> \"comp_4\" [headlabel=\"in2\"];\n\
+         \x20   \"comp_1\" -> \"comp_0\" [headlabel=\"in2\"];\n\
+         \x20   \"comp_2\" -> \"comp_4\" [headlabel=\"in1\"];\n\
+         \x20   \"comp_4\" -> \"comp_0\" [headlabel=\"in1\"];\n}\n"
+    );
+}
+
+#[test]
+fn subcircuits_show_their_ports() {
+    let dot = CircParser::new("tests/adder.circ")
+        .transpile_to_dot()
+        .unwrap();
+
+    assert!(dot.contains("    \"comp_8\" [label=\"FullAdder\\nadder0\", shape=component];\n"));
+    assert!(dot.contains("    \"comp_8\" -> \"comp_9\" [taillabel=\"Cout\", headlabel=\"Cin\"];\n"));
+    assert!(dot.contains("    { rank=sink; \"comp_5\"; \"comp_6\"; \"comp_7\"; }\n"));
+}
+
+#[test]
+fn quotes_labels() {
+    let code = "#define a\n- type: PIN\n- label: say \"hi\"\n\n#define g\n- type: NOT\n- label: inv\n\n#attach a g\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let dot = circuit.to_dot();
+
+    assert!(dot.contains("    \"a\" [label=\"say \\\"hi\\\"\", shape=square];\n"));
+    assert!(dot.contains("    \"g\" [label=\"NOT\\ninv\", shape=box, style=rounded];\n"));
+    assert!(dot.contains("    \"a\" -> \"g\";\n"));
+}
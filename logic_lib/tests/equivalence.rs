@@ -0,0 +1,160 @@
+use logic_lib::{
+    circ_parser::CircParser,
+    circuit::Circuit,
+    equivalence::{Counterexample, Equivalence, Method},
+    error::EquivalenceError,
+    logic_parser::LogicParser,
+    simulator::Value,
+};
+
+fn full_adder() -> Circuit {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+    project.flatten("FullAdder").unwrap()
+}
+
+/// A full adder with a single three-input XOR for the sum, which is only right with the
+/// XOR gate set to odd parity.
+fn submission(xor: &str) -> Circuit {
+    let code = format!(
+        "#define a\n- type: PIN\n- label: A\n\n#define b\n- type: PIN\n- label: B\n\n\
+         #define c\n- type: PIN\n- label: Cin\n\n\
+         #define x\n- type: XOR\n- inputs: 3\n- xor: {}\n\n\
+         #define ab\n- type: AND\n\n#define ac\n- type: AND\n\n#define bc\n- type: AND\n\n\
+         #define o\n- type: OR\n- inputs: 3\n\n\
+         #define s\n- type: PIN\n- output: true\n- label: S\n\n\
+         #define cout\n- type: PIN\n- output: true\n- label: Cout\n\n\
+         #attach a.out x.in1\n#attach b.out x.in2\n#attach c.out x.in3\n#attach x.out s.in\n\
+         #attach a.out ab.in1\n#attach b.out ab.in2\n#attach a.out ac.in1\n#attach c.out ac.in2\n\
+         #attach b.out bc.in1\n#attach c.out bc.in2\n\
+         #attach ab.out o.in1\n#attach ac.out o.in2\n#attach bc.out o.in3\n#attach o.out cout.in\n",
+        xor
+    );
+    code.parse::<LogicParser>().unwrap().parse().unwrap()
+}
+
+#[test]
+fn equivalent_full_adders() {
+    let reference = full_adder();
+    let submission = submission("odd");
+
+    for method in [Method::Exhaustive, Method::Bdd] {
+        assert_eq!(
+            reference.check_equivalence_with(&submission, method),
+            Ok(Equivalence::Equivalent),
+            "{:?}",
+            method
+        );
+    }
+}
+
+#[test]
+fn finds_a_counterexample() {
+    let reference = full_adder();
+    let submission = submission("1");
+
+    let expected = Equivalence::Different(Counterexample {
+        inputs: vec![
+            (String::from("A"), true),
+            (String::from("B"), true),
+            (String::from("Cin"), true),
+        ],
+        output: String::from("S"),
+        reference: Value::One,
+        submission: Value::Zero,
+    });
+    for method in [Method::Exhaustive, Method::Bdd] {
+        assert_eq!(
+            reference.check_equivalence_with(&submission, method),
+            Ok(expected.clone()),
+            "{:?}",
+            method
+        );
+    }
+
+    let Equivalence::Different(counterexample) = expected else {
+        unreachable!()
+    };
+    assert_eq!(
+        counterexample.to_string(),
+        "with A=1 B=1 Cin=1, S is 0 instead of 1"
+    );
+}
+
+#[test]
+fn wide_circuits_use_bdds() {
+    // A 16-input AND against a tree of two-input ANDs, with and without a negated input
+    let wide_and = |negate: bool| {
+        let mut code = String::new();
+        for pin in 0..16 {
+            code.push_str(&format!("#define i{}\n- type: PIN\n\n", pin));
+        }
+        code.push_str("#define q\n- type: PIN\n- output: true\n\n");
+        code.push_str("#define g\n- type: AND\n- inputs: 16\n");
+        if negate {
+            code.push_str("- negate15: true\n");
+        }
+        code.push('\n');
+        for pin in 0..16 {
+            code.push_str(&format!("#attach i{}.out g.in{}\n", pin, pin + 1));
+        }
+        code.push_str("#attach g.out q.in\n");
+        code.parse::<LogicParser>().unwrap().parse().unwrap()
+    };
+
+    let mut code = String::new();
+    for pin in 0..16 {
+        code.push_str(&format!("#define i{}\n- type: PIN\n\n", pin));
+    }
+    for gate in 1..16 {
+        code.push_str(&format!("#define g{}\n- type: AND\n\n", gate));
+    }
+    code.push_str("#define q\n- type: PIN\n- output: true\n\n");
+    // Gate n reads gates 2n and 2n + 1, the leaves read the pins
+    for gate in 1..16 {
+        for (child, port) in [(2 * gate, "in1"), (2 * gate + 1, "in2")] {
+            if child < 16 {
+                code.push_str(&format!("#attach g{}.out g{}.{}\n", child, gate, port));
+            } else {
+                code.push_str(&format!("#attach i{}.out g{}.{}\n", child - 16, gate, port));
+            }
+        }
+    }
+    code.push_str("#attach g1.out q.in\n");
+    let tree: Circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+
+    assert_eq!(
+        wide_and(false).check_equivalence(&tree),
+        Ok(Equivalence::Equivalent)
+    );
+
+    let Ok(Equivalence::Different(counterexample)) = wide_and(true).check_equivalence(&tree) else {
+        panic!("the negated input went unnoticed");
+    };
+    assert_eq!(counterexample.inputs.len(), 16);
+    assert_ne!(counterexample.reference, counterexample.submission);
+}
+
+#[test]
+fn rejects_mismatched_pins_and_latches_for_bdds() {
+    let latch = CircParser::new("tests/test.circ").parse().unwrap();
+
+    assert!(matches!(
+        full_adder().check_equivalence(&latch),
+        Err(EquivalenceError::InputsDiffer { .. })
+    ));
+    assert_eq!(latch.check_equivalence(&latch), Ok(Equivalence::Equivalent));
+    assert!(matches!(
+        latch.check_equivalence_with(&latch, Method::Bdd),
+        Err(EquivalenceError::Sequential(_))
+    ));
+}
+
+#[test]
+fn compares_projects_by_their_main_circuits() {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+
+    assert_eq!(
+        project.check_equivalence(&project),
+        Ok(Equivalence::Equivalent)
+    );
+}
@@ -0,0 +1,97 @@
+use logic_lib::{
+    circ_parser::CircParser,
+    error::NetlistError,
+    expression::{Expression, Syntax},
+    logic_parser::LogicParser,
+};
+
+#[test]
+fn latch_refers_to_its_own_output() {
+    let circuit = CircParser::new("tests/test.circ").parse().unwrap();
+    let equations = circuit.expressions().unwrap();
+
+    assert_eq!(equations.len(), 1);
+    let latch = &equations[0];
+    assert_eq!(latch.output, "comp_3");
+    assert_eq!(latch.cycles, [["comp_0", "comp_4"]]);
+
+    assert_eq!(
+        latch.to_string(),
+        "comp_3 = NOT (NOT (comp_2 OR comp_3) OR comp_1)"
+    );
+    assert_eq!(
+        latch.render(Syntax::C),
+        "comp_3 = !(!(comp_2 | comp_3) | comp_1)"
+    );
+    assert_eq!(
+        latch.render(Syntax::Latex),
+        "\\mathrm{comp\\_3} = \\overline{\\overline{\\mathrm{comp\\_2} \\lor \\mathrm{comp\\_3}} \\lor \\mathrm{comp\\_1}}"
+    );
+}
+
+#[test]
+fn flattened_adder_has_no_cycles() {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+    let equations = project.flatten("main").unwrap().expressions().unwrap();
+
+    let rendered: Vec<String> = equations
+        .iter()
+        .map(|equation| equation.render(Syntax::C))
+        .collect();
+    assert_eq!(
+        rendered,
+        [
+            "S0 = (A0 ^ B0) ^ C0",
+            "S1 = (A1 ^ B1) ^ ((A0 & B0) | ((A0 ^ B0) & C0))",
+            "C2 = (A1 & B1) | ((A1 ^ B1) & ((A0 & B0) | ((A0 ^ B0) & C0)))",
+        ]
+    );
+    assert!(equations.iter().all(|equation| equation.cycles.is_empty()));
+
+    let error = project.main_circuit().unwrap().expressions().unwrap_err();
+    assert!(matches!(error, NetlistError::UnsupportedComponent(_)));
+}
+
+#[test]
+fn follows_gate_attributes_and_open_inputs() {
+    let code = "#define a\n- type: PIN\n\n#define b\n- type: PIN\n\n#define c\n- type: PIN\n\n\
+                #define x\n- type: XOR\n- inputs: 3\n- negate0: true\n\n\
+                #define g\n- type: AND\n\n\
+                #define q\n- type: PIN\n- output: true\n\n#define r\n- type: PIN\n- output: true\n\n\
+                #attach a.out x.in1\n#attach b.out x.in2\n#attach c.out x.in3\n\
+                #attach x.out q.in\n#attach a.out g.in2\n#attach g.out r.in\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let equations = circuit.expressions().unwrap();
+
+    let a = || Expression::Variable(String::from("a"));
+    assert_eq!(
+        equations[0].to_string(),
+        "q = (NOT a AND NOT b AND NOT c) OR (NOT NOT a AND b AND NOT c) OR (NOT NOT a AND NOT b AND c)"
+    );
+    assert_eq!(equations[1].expression, a());
+}
+
+#[test]
+fn walks_long_chains_of_gates() {
+    let gates = 20_000;
+    let mut code =
+        String::from("#define a\n- type: PIN\n\n#define y\n- type: PIN\n- output: true\n\n");
+    for gate in 0..gates {
+        code.push_str(&format!("#define g{}\n- type: NOT\n\n", gate));
+    }
+    code.push_str("#attach a.out g0.in\n");
+    for gate in 1..gates {
+        code.push_str(&format!("#attach g{}.out g{}.in\n", gate - 1, gate));
+    }
+    code.push_str(&format!("#attach g{}.out y.in\n", gates - 1));
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+
+    let mut expression = &circuit.expressions().unwrap()[0].expression;
+    let mut depth = 0;
+    while let Expression::Not(operand) = expression {
+        expression = operand;
+        depth += 1;
+    }
+    assert_eq!(depth, gates);
+    assert_eq!(*expression, Expression::Variable(String::from("a")));
+}
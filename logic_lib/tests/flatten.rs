@@ -0,0 +1,81 @@
+use logic_lib::{circ_parser::CircParser, error::FlattenError, project::Project};
+
+/// A project of circuits `a` to `c`, each holding instances of the circuits given for it.
+fn project(instances: [&[&str]; 3]) -> Project {
+    let mut text = String::from("<project source=\"2.16.1.4.jar\" version=\"1.0\">\n");
+    for (name, instances) in ["a", "b", "c"].iter().zip(instances) {
+        text.push_str(&format!("<circuit name=\"{}\">\n", name));
+        for (index, instance) in instances.iter().enumerate() {
+            text.push_str(&format!(
+                "<comp loc=\"({},100)\" name=\"{}\"/>\n",
+                100 * (index + 1),
+                instance
+            ));
+        }
+        text.push_str("</circuit>\n");
+    }
+    text.push_str("</project>\n");
+
+    text.parse::<CircParser>().unwrap().parse_project().unwrap()
+}
+
+#[test]
+fn ids_are_prefixed_by_the_instance_path() {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+    let flat = project.flatten("main").unwrap();
+
+    let ids: Vec<&str> = flat
+        .components
+        .iter()
+        .map(|component| component.id.as_str())
+        .collect();
+    // Only the pins of `main` are kept, and the gates of each adder go under its label
+    assert_eq!(
+        ids[..8],
+        [
+            "main/comp_0",
+            "main/comp_1",
+            "main/comp_2",
+            "main/comp_3",
+            "main/comp_4",
+            "main/comp_5",
+            "main/comp_6",
+            "main/comp_7"
+        ]
+    );
+    assert_eq!(ids[8], "main/adder0/comp_5");
+    assert_eq!(ids[13], "main/adder1/comp_5");
+    assert_eq!(ids.len(), 18);
+    assert!(flat
+        .nets
+        .iter()
+        .flat_map(|net| &net.ports)
+        .all(|port| ids.contains(&port.component.as_str())));
+    assert!(flat.wires.is_empty());
+}
+
+#[test]
+fn recursive_circuits_are_an_error() {
+    let recursive = project([&["b"], &["c"], &["a"]]);
+    assert_eq!(
+        recursive.flatten("a"),
+        Err(FlattenError::RecursiveCircuit(vec![
+            String::from("a"),
+            String::from("b"),
+            String::from("c"),
+            String::from("a"),
+        ]))
+    );
+    assert_eq!(
+        recursive.flatten("a").unwrap_err().to_string(),
+        "circuit contains itself: a -> b -> c -> a"
+    );
+
+    // Using a circuit twice is fine as long as it does not contain itself
+    let shared = project([&["b", "c"][..], &["c"], &[]]);
+    assert!(shared.flatten("a").is_ok());
+    assert_eq!(
+        shared.flatten("d"),
+        Err(FlattenError::UnknownCircuit(String::from("d")))
+    );
+}
@@ -0,0 +1,147 @@
+use std::time::{Duration, Instant};
+
+use logic_lib::{circ_parser::CircParser, error::LogicParseError, logic_parser::LogicParser};
+
+/// An input pin, a chain of `gates` NOT gates and an output pin.
+fn not_chain(gates: usize) -> String {
+    let mut code = String::from("#define a\n- type: PIN\n\n");
+    for gate in 0..gates {
+        code.push_str(&format!("#define g{}\n- type: NOT\n\n", gate));
+    }
+    code.push_str("#define y\n- type: PIN\n- output: true\n\n");
+
+    code.push_str("#attach a.out g0.in\n");
+    for gate in 1..gates {
+        code.push_str(&format!("#attach g{}.out g{}.in\n", gate - 1, gate));
+    }
+    code.push_str(&format!("#attach g{}.out y.in\n", gates - 1));
+
+    code
+}
+
+#[test]
+fn round_trips_test_logic() {
+    let code = std::fs::read_to_string("tests/test.logic").unwrap();
+    let circuit = LogicParser::new("tests/test.logic").parse().unwrap();
+
+    assert_eq!(circuit.name, "test");
+    assert_eq!(circuit.components.len(), 5);
+    assert_eq!(circuit.to_logic_code(), code);
+}
+
+#[test]
+fn round_trips_every_module_of_a_project() {
+    let mut circ_parser = CircParser::new("tests/adder.circ");
+    let mut modules = circ_parser.transpile_all_to_logic_code().unwrap();
+    modules.insert(
+        String::from("flat"),
+        circ_parser.transpile_flat_to_logic_code().unwrap(),
+    );
+
+    for (name, code) in modules {
+        let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+        assert_eq!(circuit.to_logic_code(), code, "module {}", name);
+    }
+}
+
+#[test]
+fn parsing_time_grows_linearly_with_the_code() {
+    // The fastest of a few runs, to keep scheduling noise out of the comparison
+    let parse_time = |code: &str| -> Duration {
+        (0..3)
+            .map(|_| {
+                let start = Instant::now();
+                code.parse::<LogicParser>().unwrap().parse().unwrap();
+                start.elapsed()
+            })
+            .min()
+            .unwrap()
+    };
+    let small = parse_time(&not_chain(2_000));
+    let large = parse_time(&not_chain(16_000));
+
+    // Eight times the gates take about eight times as long to parse, quadratic parsing 64 times
+    assert!(
+        large < small * 24,
+        "2000 gates took {:?}, 16000 gates {:?}",
+        small,
+        large
+    );
+}
+
+#[test]
+fn attaches_named_ports() {
+    let code = "#define a\n- type: PIN\n\n#define b\n- type: AND\n- inputs: 3\n\n\
+                #define out/y\n- type: PIN\n- output: true\n\n\
+                #attach a.out b.in1\n#attach a.out b.in3\n#attach b.out out/y.in\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+
+    let first = &circuit.connections[0];
+    assert_eq!(first.from, "a");
+    assert_eq!(first.from_port.as_deref(), Some("out"));
+    assert_eq!(first.to, "b");
+    assert_eq!(first.to_port.as_deref(), Some("in1"));
+
+    let ends: Vec<(String, String)> = circuit
+        .connections
+        .iter()
+        .map(|connection| (connection.source(), connection.target()))
+        .collect();
+    assert_eq!(
+        ends,
+        [
+            (String::from("a.out"), String::from("b.in1")),
+            (String::from("a.out"), String::from("b.in3")),
+            (String::from("b.out"), String::from("out/y.in")),
+        ]
+    );
+}
+
+#[test]
+fn accepts_connections_without_ports() {
+    let code = "#define a\n- type: PIN\n\n#define b\n- type: NOT\n\n#attach a b.in\n#attach a b\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+
+    let connections: Vec<(String, String)> = circuit
+        .connections
+        .iter()
+        .map(|connection| (connection.source(), connection.target()))
+        .collect();
+    assert_eq!(
+        connections,
+        [
+            (String::from("a"), String::from("b.in")),
+            (String::from("a"), String::from("b")),
+        ]
+    );
+}
+
+#[test]
+fn reports_line_and_column() {
+    let parse = |code: &str| {
+        code.parse::<LogicParser>()
+            .unwrap()
+            .with_file_name("broken.logic")
+            .parse()
+            .unwrap_err()
+    };
+
+    let error = parse("#define a\n- type:   LATCH\n");
+    assert!(matches!(error, LogicParseError::UnknownType { .. }));
+    assert_eq!(error.position(), Some((2, 11)));
+    assert_eq!(
+        error.to_string(),
+        "broken.logic:2:11: unknown component type \"LATCH\""
+    );
+
+    let error = parse("#define a\n- type: PIN\n#define b\n- type: AND\n#attach a.out b.in3\n");
+    assert!(matches!(error, LogicParseError::UnknownPort { .. }));
+    assert_eq!(error.position(), Some((5, 17)));
+
+    let error = parse("#define a\n- type: PIN\n\n  #connect a a\n");
+    assert!(matches!(error, LogicParseError::Syntax { .. }));
+    assert_eq!(error.position(), Some((4, 3)));
+
+    let error = parse("- type: PIN\n");
+    assert_eq!(error.position(), Some((1, 1)));
+}
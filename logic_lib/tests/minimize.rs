@@ -0,0 +1,155 @@
+use logic_lib::{
+    circ_parser::CircParser,
+    error::MinimizeError,
+    expression::{Expression, Syntax},
+    logic_parser::LogicParser,
+    minimize::Function,
+};
+
+fn inputs(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| String::from(*name)).collect()
+}
+
+#[test]
+fn uses_dont_cares() {
+    let function = Function::new(
+        inputs(&["A", "B", "C", "D"]),
+        vec![1, 3, 7, 11, 15],
+        vec![0, 2, 5],
+    )
+    .unwrap();
+    let minimized = function.minimize().unwrap();
+
+    assert!(minimized.exact);
+    assert_eq!(
+        minimized.sum_of_products.render(Syntax::C),
+        "(C & D) | (!A & D)"
+    );
+    assert_eq!(minimized.sum_of_products_gates, 4);
+    assert_eq!(minimized.product_of_sums.render(Syntax::C), "D & (!A | C)");
+    assert_eq!(minimized.product_of_sums_gates, 3);
+    assert_eq!(minimized.gates(), 3);
+
+    let without =
+        Function::new(inputs(&["A", "B", "C", "D"]), vec![1, 3, 7, 11, 15], vec![]).unwrap();
+    assert_eq!(
+        without
+            .minimize()
+            .unwrap()
+            .sum_of_products
+            .render(Syntax::C),
+        "(C & D) | (!A & !B & D)"
+    );
+}
+
+#[test]
+fn constant_functions() {
+    let zero = Function::new(inputs(&["A", "B"]), vec![], vec![0])
+        .unwrap()
+        .minimize()
+        .unwrap();
+    assert_eq!(zero.sum_of_products, Expression::Constant(false));
+    assert_eq!(zero.product_of_sums, Expression::Constant(false));
+
+    let one = Function::new(inputs(&["A", "B"]), vec![0, 1, 2], vec![3])
+        .unwrap()
+        .minimize()
+        .unwrap();
+    assert_eq!(one.sum_of_products, Expression::Constant(true));
+    assert_eq!(one.product_of_sums, Expression::Constant(true));
+    assert_eq!(one.gates(), 0);
+}
+
+#[test]
+fn reports_the_gate_count_gap() {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+    let report = project.flatten("FullAdder").unwrap().minimize().unwrap();
+
+    assert_eq!(report.gates, 5);
+    let (output, carry) = &report.outputs[1];
+    assert_eq!(output, "Cout");
+    assert_eq!(
+        carry.sum_of_products.to_string(),
+        "(A AND B) OR (A AND Cin) OR (B AND Cin)"
+    );
+    assert_eq!(
+        carry.product_of_sums.to_string(),
+        "(A OR B) AND (A OR Cin) AND (B OR Cin)"
+    );
+    assert_eq!(carry.gates(), 4);
+
+    // Two-level forms of the sum need every minterm, where the drawing uses XOR gates
+    assert_eq!(report.minimal_gates(), 12);
+    assert_eq!(report.gap(), 0);
+}
+
+#[test]
+fn bloated_circuits_have_gates_to_save() {
+    // `y = NOT NOT (a AND (a OR b))`, which is just `a`, and `z = a OR b` drawn twice over
+    let code = "#define a\n- type: PIN\n\n#define b\n- type: PIN\n\n\
+                #define g1\n- type: OR\n\n#define g2\n- type: AND\n\n\
+                #define g3\n- type: NOT\n\n#define g4\n- type: NOT\n\n\
+                #define g5\n- type: NOR\n\n#define g6\n- type: NOT\n\n\
+                #define y\n- type: PIN\n- output: true\n\n#define z\n- type: PIN\n- output: true\n\n\
+                #attach a.out g1.in1\n#attach b.out g1.in2\n\
+                #attach a.out g2.in1\n#attach g1.out g2.in2\n\
+                #attach g2.out g3.in\n#attach g3.out g4.in\n#attach g4.out y.in\n\
+                #attach a.out g5.in1\n#attach b.out g5.in2\n#attach g5.out g6.in\n#attach g6.out z.in\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let report = circuit.minimize().unwrap();
+
+    assert_eq!(report.gates, 6);
+    assert_eq!(report.outputs[0].1.sum_of_products.to_string(), "a");
+    assert_eq!(report.outputs[1].1.sum_of_products.to_string(), "a OR b");
+    assert_eq!(report.minimal_gates(), 1);
+    assert_eq!(report.gap(), 5);
+}
+
+#[test]
+fn refuses_rows_out_of_range() {
+    assert_eq!(
+        Function::new(inputs(&["A", "B"]), vec![1, 4], vec![]),
+        Err(MinimizeError::RowOutOfRange { row: 4, inputs: 2 })
+    );
+    assert_eq!(
+        Function::new(inputs(&["A"]), vec![], vec![2]),
+        Err(MinimizeError::RowOutOfRange { row: 2, inputs: 1 })
+    );
+
+    let function = Function {
+        inputs: inputs(&["A"]),
+        ones: vec![0, 2],
+        dont_cares: vec![],
+    };
+    assert_eq!(
+        function.minimize(),
+        Err(MinimizeError::RowOutOfRange { row: 2, inputs: 1 })
+    );
+
+    let names: Vec<String> = (0..usize::BITS)
+        .map(|input| format!("I{}", input))
+        .collect();
+    assert_eq!(
+        Function::new(names, vec![0], vec![]),
+        Err(MinimizeError::TooManyInputs {
+            inputs: usize::BITS as usize,
+            max: usize::BITS as usize - 1,
+        })
+    );
+}
+
+#[test]
+fn refuses_feedback_loops() {
+    // The NOR latch outputs its state, which no function of its inputs gives
+    let circuit = CircParser::new("tests/test.circ").parse().unwrap();
+    let error = circuit.minimize().unwrap_err();
+
+    assert_eq!(
+        error,
+        MinimizeError::Sequential(vec![String::from("comp_0"), String::from("comp_4")])
+    );
+    assert_eq!(
+        error.to_string(),
+        "comp_0, comp_4 form a feedback loop, so the circuit has no truth table to minimize"
+    );
+}
@@ -0,0 +1,364 @@
+use std::time::{Duration, Instant};
+
+use logic_lib::{
+    circ_parser::CircParser,
+    error::{NetlistError, SimulationError},
+    logic_parser::LogicParser,
+    project::{GateUndefined, Options},
+    simulator::{DelayModel, Outcome, Simulator, Value},
+    types::ComponentType,
+};
+
+#[test]
+fn nor_latch_keeps_its_state() {
+    let circuit = CircParser::new("tests/test.circ").parse().unwrap();
+    let mut simulator = Simulator::new(&circuit).unwrap();
+
+    // comp_1 resets the latch and comp_2 sets it; comp_3 shows its state
+    let mut pulse = |pin: &str| {
+        simulator.set_input(pin, true).unwrap();
+        assert!(simulator.run().is_stable());
+        simulator.set_input(pin, false).unwrap();
+        assert!(simulator.run().is_stable());
+        simulator.output("comp_3").unwrap()
+    };
+
+    assert_eq!(pulse("comp_1"), Value::Zero);
+    assert_eq!(pulse("comp_2"), Value::One);
+    assert_eq!(pulse("comp_2"), Value::One);
+    assert_eq!(pulse("comp_1"), Value::Zero);
+}
+
+#[test]
+fn flattened_adder_adds() {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+    let circuit = project.flatten("main").unwrap();
+    let mut simulator = Simulator::new(&circuit).unwrap();
+
+    for inputs in 0..32_u32 {
+        let bit = |index: u32| inputs & (1 << index) != 0;
+        for (index, pin) in ["A0", "B0", "C0", "A1", "B1"].into_iter().enumerate() {
+            simulator.set_input(pin, bit(index as u32)).unwrap();
+        }
+        assert!(simulator.run().is_stable());
+
+        let a = bit(0) as u32 + 2 * bit(3) as u32;
+        let b = bit(1) as u32 + 2 * bit(4) as u32;
+        let sum = a + b + bit(2) as u32;
+        let outputs = simulator.outputs();
+
+        assert_eq!(
+            outputs["S0"],
+            Value::from(sum & 1 != 0),
+            "inputs {:05b}",
+            inputs
+        );
+        assert_eq!(
+            outputs["S1"],
+            Value::from(sum & 2 != 0),
+            "inputs {:05b}",
+            inputs
+        );
+        assert_eq!(
+            outputs["C2"],
+            Value::from(sum & 4 != 0),
+            "inputs {:05b}",
+            inputs
+        );
+    }
+}
+
+#[test]
+fn accepts_connections_without_ports_into_single_inputs() {
+    let code = "#define a\n- type: PIN\n\n#define b\n- type: NOT\n\n#define c\n- type: PIN\n- output: true\n\n#attach a b\n#attach b c\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let mut simulator = Simulator::new(&circuit).unwrap();
+
+    assert!(simulator.run().is_stable());
+    assert_eq!(simulator.output("c"), Some(Value::One));
+
+    simulator.set_input("a", true).unwrap();
+    assert!(simulator.run().is_stable());
+    assert_eq!(simulator.output("c"), Some(Value::Zero));
+}
+
+#[test]
+fn setup_time_grows_linearly_with_the_circuit() {
+    // A pin feeding a chain of NOT gates, each of which also drives the output pin
+    let chain = |gates: usize| {
+        let mut code =
+            String::from("#define a\n- type: PIN\n\n#define y\n- type: PIN\n- output: true\n\n");
+        for gate in 0..gates {
+            code.push_str(&format!("#define g{}\n- type: NOT\n\n", gate));
+        }
+        code.push_str("#attach a.out g0.in\n");
+        for gate in 1..gates {
+            code.push_str(&format!("#attach g{}.out g{}.in\n", gate - 1, gate));
+        }
+        for gate in 0..gates {
+            code.push_str(&format!("#attach g{}.out y.in\n", gate));
+        }
+        code.parse::<LogicParser>().unwrap().parse().unwrap()
+    };
+    // The fastest of a few runs, to keep scheduling noise out of the comparison
+    let setup_time = |gates: usize| -> Duration {
+        let circuit = chain(gates);
+        (0..3)
+            .map(|_| {
+                let start = Instant::now();
+                Simulator::new(&circuit).unwrap();
+                start.elapsed()
+            })
+            .min()
+            .unwrap()
+    };
+    let small = setup_time(2_000);
+    let large = setup_time(16_000);
+
+    assert!(
+        large < small * 24,
+        "2000 gates took {:?}, 16000 gates {:?}",
+        small,
+        large
+    );
+}
+
+#[test]
+fn rejects_subcircuits_and_unknown_pins() {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+    let error = Simulator::new(project.main_circuit().unwrap()).err();
+    assert!(matches!(
+        error,
+        Some(SimulationError::Netlist(
+            NetlistError::UnsupportedComponent(_)
+        ))
+    ));
+
+    let circuit = CircParser::new("tests/test.circ").parse().unwrap();
+    let mut simulator = Simulator::new(&circuit).unwrap();
+    assert_eq!(
+        simulator.set_input("comp_3", true),
+        Err(SimulationError::UnknownPin(String::from("comp_3")))
+    );
+
+    // A label shared by two pins picks neither of them
+    let code = "#define a\n- type: PIN\n- label: x\n\n#define b\n- type: PIN\n- label: x\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let mut simulator = Simulator::new(&circuit).unwrap();
+    assert_eq!(
+        simulator.set_input("x", true),
+        Err(SimulationError::UnknownPin(String::from("x")))
+    );
+    assert_eq!(simulator.set_input("b", true), Ok(()));
+    assert_eq!(simulator.input_names(), ["a", "b"]);
+}
+
+#[test]
+fn latch_starts_unknown() {
+    let project = CircParser::new("tests/test.circ").parse_project().unwrap();
+    let mut simulator = Simulator::new(project.main_circuit().unwrap())
+        .unwrap()
+        .with_options(&project.options);
+
+    assert_eq!(project.options.gate_undefined, GateUndefined::Ignore);
+    assert!(simulator.run().is_stable());
+    assert_eq!(simulator.output("comp_3"), Some(Value::Unknown));
+}
+
+#[test]
+fn floating_inputs_follow_gate_undefined() {
+    let code = "#define a\n- type: PIN\n- tristate: true\n\n#define b\n- type: PIN\n\n\
+                #define g\n- type: AND\n\n#define q\n- type: PIN\n- output: true\n\n\
+                #attach a.out g.in1\n#attach b.out g.in2\n#attach g.out q.in\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+
+    let options = Options {
+        gate_undefined: GateUndefined::Error,
+        ..Options::default()
+    };
+    let mut ignore = Simulator::new(&circuit).unwrap();
+    let mut error = Simulator::new(&circuit).unwrap().with_options(&options);
+
+    for simulator in [&mut ignore, &mut error] {
+        simulator.set_input("a", Value::Floating).unwrap();
+        simulator.set_input("b", true).unwrap();
+        assert!(simulator.run().is_stable());
+    }
+
+    assert_eq!(ignore.output("q"), Some(Value::One));
+    assert_eq!(error.output("q"), Some(Value::Unknown));
+    assert_eq!(
+        ignore.set_input("b", Value::Floating),
+        Err(SimulationError::InvalidValue {
+            pin: String::from("b"),
+            value: Value::Floating
+        })
+    );
+}
+
+#[test]
+fn released_latch_oscillates() {
+    let project = CircParser::new("tests/test.circ").parse_project().unwrap();
+    let mut simulator = Simulator::new(project.main_circuit().unwrap())
+        .unwrap()
+        .with_options(&project.options);
+
+    simulator.set_input("comp_1", true).unwrap();
+    simulator.set_input("comp_2", true).unwrap();
+    assert!(simulator.run().is_stable());
+
+    simulator.set_input("comp_1", false).unwrap();
+    simulator.set_input("comp_2", false).unwrap();
+    let Outcome::Oscillation { steps, components } = simulator.run() else {
+        panic!("the latch settled");
+    };
+
+    assert_eq!(steps, project.options.sim_limit);
+    assert_eq!(components, ["comp_0", "comp_3", "comp_4"]);
+}
+
+#[test]
+fn ring_oscillator_stops_at_simlimit() {
+    let code = "#define en\n- type: PIN\n\n#define a\n- type: NAND\n\n\
+                #define b\n- type: NOT\n\n#define c\n- type: NOT\n\n\
+                #attach en.out a.in1\n#attach c.out a.in2\n#attach a.out b.in\n#attach b.out c.in\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let options = Options {
+        sim_limit: 50,
+        ..Options::default()
+    };
+    let mut simulator = Simulator::new(&circuit).unwrap().with_options(&options);
+
+    assert!(simulator.run().is_stable());
+
+    simulator.set_input("en", true).unwrap();
+    assert_eq!(
+        simulator.run(),
+        Outcome::Oscillation {
+            steps: 50,
+            components: vec![String::from("a"), String::from("b"), String::from("c")],
+        }
+    );
+}
+
+const INVERTER_CHAIN: &str = "#define a\n- type: PIN\n\n#define b\n- type: NOT\n- delay: 3\n\n\
+                              #define c\n- type: NOT\n\n#define q\n- type: PIN\n- output: true\n\n\
+                              #attach a.out b.in\n#attach b.out c.in\n#attach c.out q.in\n";
+
+#[test]
+fn delays_come_from_attributes_and_types() {
+    let circuit = INVERTER_CHAIN
+        .parse::<LogicParser>()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let mut simulator = Simulator::new(&circuit)
+        .unwrap()
+        .with_delay(ComponentType::NOT, 2);
+
+    assert!(simulator.run().is_stable());
+    let settled = simulator.time();
+
+    simulator.set_input("a", true).unwrap();
+    assert_eq!(
+        simulator.run_until(settled + 4),
+        Outcome::Pending { steps: 1 }
+    );
+    assert_eq!(simulator.output("q"), Some(Value::Zero));
+
+    assert!(simulator.run().is_stable());
+    assert_eq!(simulator.time(), settled + 5);
+    assert_eq!(simulator.output("q"), Some(Value::One));
+}
+
+#[test]
+fn inertial_delay_swallows_short_pulses() {
+    let circuit = INVERTER_CHAIN
+        .parse::<LogicParser>()
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    for (delay_model, during_pulse) in [
+        (DelayModel::Transport, Value::One),
+        (DelayModel::Inertial, Value::Zero),
+    ] {
+        let mut simulator = Simulator::new(&circuit)
+            .unwrap()
+            .with_delay_model(delay_model);
+        assert!(simulator.run().is_stable());
+        let start = simulator.time();
+
+        // A pulse of 2 into `b`, which takes 3 to react
+        simulator.set_input("a", true).unwrap();
+        simulator.run_until(start + 2);
+        simulator.set_input("a", false).unwrap();
+
+        simulator.run_until(start + 4);
+        assert_eq!(
+            simulator.output("q"),
+            Some(during_pulse),
+            "{:?}",
+            delay_model
+        );
+
+        assert!(simulator.run().is_stable());
+        assert_eq!(simulator.output("q"), Some(Value::Zero));
+    }
+}
+
+#[test]
+fn simrand_jitter_is_reproducible() {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+    let circuit = project.flatten("main").unwrap();
+    let options = Options {
+        sim_rand: 32,
+        ..project.options.clone()
+    };
+
+    let times = |seed: u64| {
+        let mut simulator = Simulator::new(&circuit)
+            .unwrap()
+            .with_options(&options)
+            .with_seed(seed);
+        let mut times = Vec::new();
+
+        for pin in ["A0", "B0", "C0", "A1", "B1"] {
+            simulator.set_input(pin, true).unwrap();
+            assert!(simulator.run().is_stable());
+            times.push(simulator.time());
+        }
+
+        assert_eq!(simulator.output("S0"), Some(Value::One));
+        assert_eq!(simulator.output("S1"), Some(Value::One));
+        assert_eq!(simulator.output("C2"), Some(Value::One));
+        times
+    };
+
+    assert_eq!(times(7), times(7));
+    assert_ne!(times(7), times(8));
+
+    // A single gate with a delay of 3 takes between 3 * 32 and 3 * 32 + 31 to switch
+    let code =
+        "#define a\n- type: PIN\n\n#define g\n- type: NOT\n- delay: 3\n\n#attach a.out g.in\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let mut simulator = Simulator::new(&circuit)
+        .unwrap()
+        .with_options(&options)
+        .with_seed(7);
+    assert!(simulator.run().is_stable());
+
+    let mut delays = Vec::new();
+    for toggle in 0..50 {
+        let start = simulator.time();
+        simulator.set_input("a", toggle % 2 == 0).unwrap();
+        assert!(simulator.run().is_stable());
+        delays.push(simulator.time() - start);
+    }
+    assert!(
+        delays.iter().all(|delay| (96..128).contains(delay)),
+        "{:?}",
+        delays
+    );
+    assert!(delays.iter().any(|&delay| delay != delays[0]));
+}
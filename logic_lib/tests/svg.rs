@@ -0,0 +1,72 @@
+use logic_lib::{circ_parser::CircParser, logic_parser::LogicParser};
+
+#[test]
+fn latch_picture() {
+    let svg = CircParser::new("tests/test.circ")
+        .transpile_to_svg()
+        .unwrap();
+
+    assert!(svg.starts_with(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"180\" height=\"140\" viewBox=\"10 5 180 140\">\n"
+    ));
+    assert!(svg.contains("    <line x1=\"50\" y1=\"130\" x2=\"80\" y2=\"130\"/>\n"));
+    assert!(svg
+        .contains("  <g fill=\"#006400\">\n    <circle cx=\"130\" cy=\"120\" r=\"4\"/>\n  </g>\n"));
+    assert!(svg.contains(
+        "    <g transform=\"translate(120,30)\"><path d=\"M-40,-15 Q-20,-15 -10,0 Q-20,15 -40,15 Q-32,0 -40,-15 Z\"/><circle cx=\"-5\" cy=\"0\" r=\"5\"/></g>\n"
+    ));
+    assert!(svg.contains("    <rect x=\"20\" y=\"20\" width=\"20\" height=\"20\"/>\n"));
+    assert!(svg.contains("    <rect x=\"160\" y=\"60\" width=\"20\" height=\"20\" rx=\"6\"/>\n"));
+    assert!(svg.ends_with("  </g>\n</svg>\n"));
+}
+
+#[test]
+fn subcircuits_are_boxes_around_their_ports() {
+    let svg = CircParser::new("tests/adder.circ")
+        .transpile_to_svg()
+        .unwrap();
+
+    assert!(svg.contains("    <rect x=\"170\" y=\"90\" width=\"30\" height=\"40\"/>\n"));
+    assert!(svg.contains("    <text x=\"185\" y=\"144\" text-anchor=\"middle\">FullAdder</text>\n"));
+    assert!(svg.contains("    <text x=\"185\" y=\"86\" text-anchor=\"middle\">adder0</text>\n"));
+    assert!(svg.contains("    <text x=\"36\" y=\"44\" text-anchor=\"end\">A0</text>\n"));
+    assert!(svg.contains("    <text x=\"324\" y=\"104\">S0</text>\n"));
+}
+
+#[test]
+fn escapes_labels() {
+    let code = "#define a\n- type: PIN\n\n#define g\n- type: AND\n- label: a<b & c\n\n#attach a.out g.in1\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let svg = circuit.to_svg();
+
+    assert!(svg.contains(
+        "<g transform=\"translate(0,0)\"><path d=\"M-30,-15 H-15 A15,15 0 0 1 -15,15 H-30 Z\"/></g>"
+    ));
+    assert!(svg.contains(">a&lt;b &amp; c</text>"));
+}
+
+#[test]
+fn dots_mark_t_junctions_but_not_crossings() {
+    // A wire ends on the middle of another at (60,20), and crosses a third at (60,40); the
+    // output pin has no `facing` attribute, so it faces east like in Logisim
+    let circ = "<project source=\"2.16.1.4.jar\" version=\"1.0\">\n\
+                <lib desc=\"#Wiring\" name=\"0\"/>\n\
+                <circuit name=\"main\">\n\
+                <wire from=\"(20,20)\" to=\"(100,20)\"/>\n\
+                <wire from=\"(60,20)\" to=\"(60,60)\"/>\n\
+                <wire from=\"(40,40)\" to=\"(80,40)\"/>\n\
+                <comp lib=\"0\" loc=\"(100,20)\" name=\"Pin\">\n<a name=\"output\" val=\"true\"/>\n</comp>\n\
+                </circuit>\n\
+                </project>\n";
+    let svg = circ
+        .parse::<CircParser>()
+        .unwrap()
+        .transpile_to_svg()
+        .unwrap();
+
+    assert!(
+        svg.contains("  <g fill=\"#006400\">\n    <circle cx=\"60\" cy=\"20\" r=\"4\"/>\n  </g>\n")
+    );
+    assert!(!svg.contains("cy=\"40\" r=\"4\""));
+    assert!(svg.contains("    <rect x=\"80\" y=\"10\" width=\"20\" height=\"20\" rx=\"6\"/>\n"));
+}
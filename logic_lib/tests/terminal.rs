@@ -0,0 +1,62 @@
+use logic_lib::{circ_parser::CircParser, terminal::Charset};
+
+#[test]
+fn latch_drawing() {
+    let circuit = CircParser::new("tests/test.circ").parse().unwrap();
+
+    assert_eq!(
+        circuit.to_terminal(Charset::Unicode),
+        "        ┌───────┐\n\
+         \x20 ┌─────┤       │\n\
+         ■─┘     │  NOR  ├─┐\n\
+         \x20     ┌─┤       │ │\n\
+         \x20     │ └───────┘ │\n\
+         \x20     │           │\n\
+         \x20   ┌─┼───────────┘   ┌─●\n\
+         \x20   │ │               │\n\
+         \x20   │ └───────────┐   │\n\
+         \x20   │   ┌───────┐ │   │\n\
+         \x20   └───┤       │ │   │\n\
+         ■─┐     │  NOR  ├─┴───┘\n\
+         \x20 └─────┤       │\n\
+         \x20       └───────┘\n"
+    );
+}
+
+#[test]
+fn ascii_subcircuits() {
+    let circuit = CircParser::new("tests/adder.circ").parse().unwrap();
+    let drawing = circuit.to_terminal(Charset::Ascii);
+
+    assert!(drawing.contains("B0 #-------------+ |     adder0\n"));
+    assert!(drawing.contains("                 | +-----+     +-------------------@ S0\n"));
+    assert!(drawing.contains("                 +-------+FullA+---+\n"));
+    assert!(drawing.is_ascii());
+}
+
+#[test]
+fn turned_gates_and_negated_inputs() {
+    let circ = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+        <project source=\"2.16.1.4.jar\" version=\"1.0\">\n\
+        <lib desc=\"#Wiring\" name=\"0\"/>\n\
+        <lib desc=\"#Gates\" name=\"1\"/>\n\
+        <main name=\"main\"/>\n\
+        <circuit name=\"main\">\n\
+        <wire from=\"(40,80)\" to=\"(60,80)\"/>\n\
+        <comp lib=\"1\" loc=\"(60,40)\" name=\"NAND Gate\">\n\
+        <a name=\"facing\" val=\"north\"/>\n\
+        <a name=\"negate0\" val=\"true\"/>\n\
+        <a name=\"label\" val=\"g\"/>\n\
+        </comp>\n\
+        <comp lib=\"0\" loc=\"(40,80)\" name=\"Pin\">\n\
+        <a name=\"label\" val=\"a\"/>\n\
+        </comp>\n\
+        </circuit>\n\
+        </project>\n";
+    let circuit = circ.parse::<CircParser>().unwrap().parse().unwrap();
+
+    assert_eq!(
+        circuit.to_terminal(Charset::Unicode),
+        "      g\n  ┌───┴───┐\n  │       │\n  │ NAND  │\n  │       │\na ■─o───┬─┘\n"
+    );
+}
@@ -0,0 +1,122 @@
+use logic_lib::{
+    circ_parser::CircParser,
+    error::TruthTableError,
+    logic_parser::LogicParser,
+    simulator::Value,
+    truth_table::{Row, TruthTable},
+};
+
+fn full_adder() -> TruthTable {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+    let circuit = project.flatten("FullAdder").unwrap();
+
+    TruthTable::new(&circuit).unwrap()
+}
+
+#[test]
+fn tabulates_the_full_adder() {
+    let table = full_adder();
+
+    assert_eq!(table.inputs, ["A", "B", "Cin"]);
+    assert_eq!(table.outputs, ["S", "Cout"]);
+    assert_eq!(table.rows.len(), 8);
+
+    for row in &table.rows {
+        let sum = row.inputs.iter().filter(|input| **input).count();
+        assert_eq!(
+            row.outputs,
+            [Value::from(sum % 2 == 1), Value::from(sum >= 2)],
+            "{:?}",
+            row.inputs
+        );
+    }
+}
+
+#[test]
+fn latch_state_shows_as_unknown() {
+    let circuit = CircParser::new("tests/test.circ").parse().unwrap();
+    let table = TruthTable::new(&circuit).unwrap();
+
+    assert_eq!(
+        table.rows,
+        [
+            Row {
+                inputs: vec![false, false],
+                outputs: vec![Value::Unknown]
+            },
+            Row {
+                inputs: vec![false, true],
+                outputs: vec![Value::One]
+            },
+            Row {
+                inputs: vec![true, false],
+                outputs: vec![Value::Zero]
+            },
+            Row {
+                inputs: vec![true, true],
+                outputs: vec![Value::Zero]
+            },
+        ]
+    );
+    assert_eq!(table.lookup(&[false, true]), Some([Value::One].as_slice()));
+}
+
+#[test]
+fn exports_markdown_csv_and_rust() {
+    let table = full_adder();
+
+    let markdown = table.to_markdown();
+    assert!(markdown.starts_with("| A | B | Cin | S | Cout |\n|---|---|---|---|---|\n"));
+    assert!(markdown.ends_with("| 1 | 1 | 1 | 1 | 1 |\n"));
+
+    let csv = table.to_csv();
+    assert_eq!(csv.lines().count(), 9);
+    assert!(csv.starts_with("A,B,Cin,S,Cout\n0,0,0,0,0\n0,0,1,1,0\n"));
+
+    let rust = table.to_rust();
+    assert!(rust.contains("pub struct FullAdderRow {\n    /// Input `A`\n    pub a: bool,\n"));
+    assert!(rust.contains("    pub cout: bool,\n}"));
+    assert!(rust.contains("pub const FULL_ADDER_TRUTH_TABLE: [FullAdderRow; 8] = [\n"));
+    assert!(
+        rust.contains("    FullAdderRow { a: true, b: true, cin: false, s: false, cout: true },\n")
+    );
+}
+
+#[test]
+fn refuses_too_many_inputs() {
+    let project = CircParser::new("tests/adder.circ").parse_project().unwrap();
+    let circuit = project.flatten("main").unwrap();
+
+    assert_eq!(
+        TruthTable::with_max_inputs(&circuit, 4),
+        Err(TruthTableError::TooManyInputs { inputs: 5, max: 4 })
+    );
+
+    // Row numbers run out of bits long before `usize::MAX` inputs
+    let mut code = String::new();
+    for pin in 0..usize::BITS {
+        code.push_str(&format!("#define p{}\n- type: PIN\n\n", pin));
+    }
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    assert_eq!(
+        TruthTable::with_max_inputs(&circuit, usize::MAX),
+        Err(TruthTableError::TooManyInputs {
+            inputs: usize::BITS as usize,
+            max: usize::BITS as usize - 1,
+        })
+    );
+}
+
+#[test]
+fn pins_sharing_a_label_go_by_their_ids() {
+    let code = "#define a\n- type: PIN\n- label: x\n\n#define b\n- type: PIN\n- label: x\n\n\
+                #define g\n- type: AND\n\n#define y\n- type: PIN\n- output: true\n- label: y\n\n\
+                #attach a.out g.in1\n#attach b.out g.in2\n#attach g.out y.in\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let table = TruthTable::new(&circuit).unwrap();
+
+    assert_eq!(table.inputs, ["a", "b"]);
+    assert_eq!(table.outputs, ["y"]);
+    assert_eq!(table.lookup(&[true, false]), Some([Value::Zero].as_slice()));
+    assert_eq!(table.lookup(&[true, true]), Some([Value::One].as_slice()));
+}
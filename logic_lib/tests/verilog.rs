@@ -0,0 +1,70 @@
+use logic_lib::{circ_parser::CircParser, circuit::Circuit, logic_parser::LogicParser};
+
+#[test]
+fn latch_module() {
+    let verilog = CircParser::new("tests/test.circ")
+        .transpile_to_verilog()
+        .unwrap();
+
+    assert_eq!(
+        verilog,
+        "module main (\n    input comp_1,\n    input comp_2,\n    output comp_3\n);\n    wire net_0;\n\n\
+         \x20   nor comp_0 (comp_3, net_0, comp_1);\n    nor comp_4 (net_0, comp_2, comp_3);\nendmodule\n"
+    );
+}
+
+#[test]
+fn subcircuits_become_module_instances() {
+    let verilog = CircParser::new("tests/adder.circ")
+        .transpile_to_verilog()
+        .unwrap();
+
+    assert!(verilog.starts_with("module FullAdder (\n    input A,\n"));
+    assert!(verilog.contains("    xor comp_7 (S, net_0, Cin);\n"));
+    assert!(verilog
+        .contains("    FullAdder comp_8 (.S(S0), .Cout(net_0), .A(A0), .B(B0), .Cin(C0));\n"));
+    assert!(verilog
+        .contains("    FullAdder comp_9 (.S(S1), .Cout(C2), .A(A1), .B(B1), .Cin(net_0));\n"));
+    assert_eq!(verilog.matches("endmodule").count(), 2);
+}
+
+#[test]
+fn escapes_names_and_expands_gate_options() {
+    let code = "#define a\n- type: PIN\n- label: wire\n\n#define b\n- type: PIN\n\n#define c\n- type: PIN\n\n\
+                #define x\n- type: XOR\n- inputs: 3\n\n\
+                #define n\n- type: NAND\n- inputs: 3\n- negate0: true\n\n\
+                #define q\n- type: PIN\n- output: true\n- label: q\n\n\
+                #define r\n- type: PIN\n- output: true\n- label: q\n\n\
+                #attach a x.in1\n#attach b.out x.in2\n#attach c.out x.in3\n#attach x.out q.in\n\
+                #attach a.out n.in1\n#attach b.out n.in2\n#attach n r\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let verilog = circuit.to_verilog();
+
+    // `wire` is a keyword, and the duplicated `q` labels fall back to the pin ids
+    assert!(verilog.starts_with(
+        "module main (\n    input \\wire ,\n    input b,\n    input c,\n    output q,\n    output r\n);\n"
+    ));
+    assert!(verilog.contains("    wire n_negate0;\n"));
+    assert!(verilog.contains(
+        "    assign q = (\\wire  & ~b & ~c) | (~\\wire  & b & ~c) | (~\\wire  & ~b & c);\n"
+    ));
+    assert!(verilog.contains("    not (n_negate0, \\wire );\n    nand n (r, n_negate0, b);\n"));
+}
+
+#[test]
+fn escaped_names_stay_distinct() {
+    let module = |name: &str| {
+        let verilog = Circuit::new(name).to_verilog();
+        String::from(verilog.lines().next().unwrap())
+    };
+
+    assert_eq!(module("half_adder"), "module half_adder (");
+    assert_eq!(module("half adder"), "module \\half\\u{20}adder  (");
+    // The backslash of an escape is escaped in turn, so no name can pass for another
+    assert_eq!(
+        module("half\\u{20}adder"),
+        "module \\half\\u{5c}u{20}adder  ("
+    );
+    // `logic` is reserved in SystemVerilog
+    assert_eq!(module("logic"), "module \\logic  (");
+}
@@ -0,0 +1,68 @@
+use logic_lib::{circ_parser::CircParser, logic_parser::LogicParser};
+
+#[test]
+fn latch_entity() {
+    let vhdl = CircParser::new("tests/test.circ")
+        .transpile_to_vhdl()
+        .unwrap();
+
+    assert!(vhdl.starts_with("library ieee;\nuse ieee.std_logic_1164.all;\n\nentity main is\n"));
+    assert!(vhdl.contains(
+        "architecture structural of main is\n    signal net_0 : std_logic;\nbegin\n\
+         \x20   comp_0 : comp_3 <= not (net_0 or comp_1);\n\
+         \x20   comp_4 : net_0 <= not (comp_2 or comp_3);\nend architecture structural;\n"
+    ));
+}
+
+#[test]
+fn subcircuits_are_analyzed_first() {
+    let vhdl = CircParser::new("tests/adder.circ")
+        .transpile_to_vhdl()
+        .unwrap();
+
+    let full_adder = vhdl.find("entity FullAdder is").unwrap();
+    let main = vhdl.find("entity main is").unwrap();
+    assert!(full_adder < main);
+    assert!(vhdl.contains("        Cin : in std_logic;\n        S : out std_logic;\n"));
+    assert!(vhdl.contains(
+        "    comp_9 : entity work.FullAdder port map (S => S1, Cout => C2, A => A1, B => B1, Cin => net_0);\n"
+    ));
+}
+
+#[test]
+fn extended_identifiers_and_gate_options() {
+    let code = "#define a\n- type: PIN\n- label: Out\n\n#define b\n- type: PIN\n\n#define c\n- type: PIN\n\n\
+                #define x\n- type: XNOR\n- inputs: 3\n- negate2: true\n\n\
+                #define n\n- type: NOT\n\n\
+                #define q\n- type: PIN\n- output: true\n- label: q\n\n\
+                #define r\n- type: PIN\n- output: true\n- label: r__1\n\n\
+                #attach a.out x.in1\n#attach b.out x.in2\n#attach c.out x.in3\n#attach x.out q.in\n\
+                #attach b n\n#attach n r\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let vhdl = circuit.to_vhdl();
+
+    assert!(vhdl.contains(
+        "    port (\n        \\Out\\ : in std_logic;\n        b : in std_logic;\n        c : in std_logic;\n\
+         \x20       q : out std_logic;\n        \\r__1\\ : out std_logic\n    );\n"
+    ));
+    assert!(vhdl.contains(
+        "    x : q <= not ((\\Out\\ and not b and not (not c)) or (not \\Out\\ and b and not (not c)) \
+         or (not \\Out\\ and not b and not c));\n"
+    ));
+    assert!(vhdl.contains("    n : \\r__1\\ <= not b;\n"));
+}
+
+#[test]
+fn pins_wired_together_are_tied_to_their_net() {
+    let code = "#define a\n- type: PIN\n\n#define b\n- type: PIN\n\n#define n\n- type: NOT\n\n\
+                #define q\n- type: PIN\n- output: true\n\n#define r\n- type: PIN\n- output: true\n\n\
+                #attach a.out n.in\n#attach b.out n.in\n#attach n.out q.in\n#attach n.out r.in\n";
+    let circuit = code.parse::<LogicParser>().unwrap().parse().unwrap();
+    let vhdl = circuit.to_vhdl();
+
+    // Both inputs drive the net, and the second output reads the first
+    assert!(vhdl.contains(
+        "    signal net_0 : std_logic;\nbegin\n    net_0 <= a;\n    net_0 <= b;\n\
+         \x20   n : q <= not net_0;\n    r <= q;\n"
+    ));
+}